@@ -1,28 +1,50 @@
 pub mod backtrace;
+mod builtins;
+pub mod class;
 pub mod closure;
 pub mod errors;
 mod expr;
+pub mod instance;
 mod lexer;
+pub mod map;
+pub mod module;
+pub mod native;
 mod npeekable;
 mod parser;
+mod pattern;
 mod stmt;
+pub mod unwind;
 pub mod vm;
 pub use self::backtrace::Backtrace;
+pub use self::class::LoxClass;
 pub use self::closure::LoxClosure;
 pub use self::errors::Errors;
 pub use self::expr::Expr;
+pub use self::instance::LoxInstance;
 pub use self::lexer::Lexer;
+pub use self::map::{LoxMap, MapKey};
+pub use self::module::{LoxModule, Source};
+pub use self::native::LoxNative;
 use self::npeekable::NPeekable;
 use self::npeekable::NPeekableExt;
 pub use self::parser::Parser;
+pub use self::unwind::Unwind;
 pub use self::vm::VM;
+use std::{cell::RefCell, rc::Rc};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub enum LoxValue {
     Bool(bool),
+    Int(i64),
     Num(f64),
     Str(String),
+    List(Rc<RefCell<Vec<LoxValue>>>),
+    Map(Rc<RefCell<LoxMap>>),
     Fn(LoxClosure),
+    Native(LoxNative),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+    Module(Rc<LoxModule>),
     Nil,
 }
 
@@ -31,57 +53,93 @@ pub struct Token {
     pub text: String,
     pub kind: TokenType,
     pub value: LoxValue,
+    // the doc comment written just before the token, if any
+    pub doc: Option<String>,
 }
 
 #[derive(Clone, Copy, Eq, PartialEq)]
 pub enum TokenType {
     // single-character tokens
+    Ampersand,
+    Caret,
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
+    Ellipsis,
     Minus,
     Percent,
+    Pipe,
     Plus,
+    Question,
     Semicolon,
     Slash,
     Star,
+    Tilde,
 
     // double-character tokens
     Bang,
     BangEqual,
     Equal,
     EqualEqual,
+    FatArrow,
     Greater,
     GreaterEqual,
+    GreaterGreater,
+    GreaterGreaterGreater,
     Less,
     LessEqual,
+    LessLess,
+    MinusEqual,
+    MinusMinus,
+    PercentEqual,
+    PlusEqual,
+    PlusPlus,
+    SlashEqual,
+    StarEqual,
+    StarStar,
+    TildeSlash,
 
     // literals
     Literal,
     Identifier,
     Number,
     String,
+    InterpolationStart,
+    InterpolationMid,
+    InterpolationEnd,
 
     // keywords
     And,
+    As,
+    Break,
+    Catch,
     Class,
+    Const,
+    Continue,
     Else,
     False,
+    Finally,
     Fn,
     For,
     If,
+    Import,
+    Match,
     Nil,
     Or,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
-    Print, // TODO: remove once functions work
 
     // sentinels
     Error,
@@ -92,22 +150,169 @@ impl LoxValue {
     pub fn is_truthy(value: &LoxValue) -> bool {
         match value {
             LoxValue::Bool(value) => *value,
+            LoxValue::Int(_) => true,
             LoxValue::Num(_) => true,
             LoxValue::Str(_) => true,
+            LoxValue::List(_) => true,
+            LoxValue::Map(_) => true,
             LoxValue::Fn(_) => true,
+            LoxValue::Native(_) => true,
+            LoxValue::Class(_) => true,
+            LoxValue::Instance(_) => true,
+            LoxValue::Module(_) => true,
             LoxValue::Nil => false,
         }
     }
 }
 
-impl std::fmt::Display for LoxValue {
-    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+impl LoxValue {
+    /// as_float: the value of a number as a float, promoting integers.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            LoxValue::Int(value) => Some(*value as f64),
+            LoxValue::Num(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// as_int: the value of a number as an integer, if it has an exact one.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            LoxValue::Int(value) => Some(*value),
+            // the bounds are -2^63 inclusive and 2^63 exclusive
+            LoxValue::Num(value)
+                if value.fract() == 0.0
+                    && *value >= -9.223372036854776e18
+                    && *value < 9.223372036854776e18 =>
+            {
+                Some(*value as i64)
+            }
+            _ => None,
+        }
+    }
+
+    fn fmt_item(
+        &self,
+        formatter: &mut std::fmt::Formatter,
+        seen: &mut Vec<usize>,
+    ) -> Result<(), std::fmt::Error> {
+        // strings inside collections are quoted so items stay distinguishable
+        match self {
+            LoxValue::Str(str) => write!(formatter, "{:?}", str),
+            _ => self.fmt_seen(formatter, seen),
+        }
+    }
+
+    /// fmt_seen: print a value, showing a list or map that contains itself as [...] or {...}.
+    /// seen holds the collections currently being printed.
+    fn fmt_seen(
+        &self,
+        formatter: &mut std::fmt::Formatter,
+        seen: &mut Vec<usize>,
+    ) -> Result<(), std::fmt::Error> {
         match self {
             LoxValue::Bool(value) => formatter.write_str(&value.to_string()),
-            LoxValue::Num(value) => formatter.write_str(&value.to_string()),
+            LoxValue::Int(value) => formatter.write_str(&value.to_string()),
+            // floats keep their fractional part, so 1.0 doesn't print like the integer 1
+            LoxValue::Num(value) => write!(formatter, "{:?}", value),
             LoxValue::Str(value) => formatter.write_str(&value),
-            LoxValue::Fn(value) => formatter.write_str("<function>"),
+            LoxValue::List(value) => {
+                let ptr = Rc::as_ptr(value) as usize;
+                if seen.contains(&ptr) {
+                    return formatter.write_str("[...]");
+                }
+                seen.push(ptr);
+                formatter.write_str("[")?;
+                for (i, item) in value.borrow().iter().enumerate() {
+                    if i != 0 {
+                        formatter.write_str(", ")?;
+                    }
+                    item.fmt_item(formatter, seen)?;
+                }
+                seen.pop();
+                formatter.write_str("]")
+            }
+            LoxValue::Map(value) => {
+                let ptr = Rc::as_ptr(value) as usize;
+                if seen.contains(&ptr) {
+                    return formatter.write_str("{...}");
+                }
+                seen.push(ptr);
+                formatter.write_str("{")?;
+                for (i, (key, value)) in value.borrow().iter().enumerate() {
+                    if i != 0 {
+                        formatter.write_str(", ")?;
+                    }
+                    key.fmt_item(formatter, seen)?;
+                    formatter.write_str(": ")?;
+                    value.fmt_item(formatter, seen)?;
+                }
+                seen.pop();
+                formatter.write_str("}")
+            }
+            LoxValue::Fn(value) => write!(formatter, "<fn {}>", value.name),
+            LoxValue::Native(value) => write!(formatter, "<native fn {}>", value.name),
+            LoxValue::Class(value) => write!(formatter, "<class {}>", value.name),
+            LoxValue::Instance(value) => {
+                write!(formatter, "<{} instance>", value.borrow().class.name)
+            }
+            LoxValue::Module(value) => write!(formatter, "<module {}>", value.name),
             LoxValue::Nil => formatter.write_str("nil"),
         }
     }
+
+    /// eq_seen: compare two values, treating a pair of lists or maps that is already being
+    /// compared as equal, so collections that contain themselves don't recurse forever.
+    fn eq_seen(&self, other: &LoxValue, seen: &mut Vec<(usize, usize)>) -> bool {
+        // integers and floats compare by value, so 1 == 1.0
+        match (self, other) {
+            (LoxValue::Bool(a), LoxValue::Bool(b)) => a == b,
+            (LoxValue::Int(a), LoxValue::Int(b)) => a == b,
+            (LoxValue::Num(a), LoxValue::Num(b)) => a == b,
+            (LoxValue::Int(a), LoxValue::Num(_)) => other.as_int() == Some(*a),
+            (LoxValue::Num(_), LoxValue::Int(b)) => self.as_int() == Some(*b),
+            (LoxValue::Str(a), LoxValue::Str(b)) => a == b,
+            (LoxValue::List(a), LoxValue::List(b)) => {
+                let pair = (Rc::as_ptr(a) as usize, Rc::as_ptr(b) as usize);
+                if Rc::ptr_eq(a, b) || seen.contains(&pair) {
+                    return true;
+                }
+                seen.push(pair);
+                let (a, b) = (a.borrow(), b.borrow());
+                let eq =
+                    a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.eq_seen(b, seen));
+                seen.pop();
+                eq
+            }
+            (LoxValue::Map(a), LoxValue::Map(b)) => {
+                let pair = (Rc::as_ptr(a) as usize, Rc::as_ptr(b) as usize);
+                if Rc::ptr_eq(a, b) || seen.contains(&pair) {
+                    return true;
+                }
+                seen.push(pair);
+                let eq = a.borrow().eq_by(&b.borrow(), |a, b| a.eq_seen(b, seen));
+                seen.pop();
+                eq
+            }
+            (LoxValue::Fn(a), LoxValue::Fn(b)) => a == b,
+            (LoxValue::Native(a), LoxValue::Native(b)) => a == b,
+            (LoxValue::Class(a), LoxValue::Class(b)) => a == b,
+            (LoxValue::Instance(a), LoxValue::Instance(b)) => a == b,
+            (LoxValue::Module(a), LoxValue::Module(b)) => a == b,
+            (LoxValue::Nil, LoxValue::Nil) => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for LoxValue {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        self.fmt_seen(formatter, &mut Vec::new())
+    }
+}
+
+impl PartialEq for LoxValue {
+    fn eq(&self, other: &LoxValue) -> bool {
+        self.eq_seen(other, &mut Vec::new())
+    }
 }
@@ -1,20 +1,51 @@
+use std::rc::Rc;
+
+use super::{LoxInstance, LoxValue, Source};
+
 pub struct Backtrace {
     backtrace: Vec<(usize, usize)>,
+    sources: Vec<Option<Rc<Source>>>,
     error: String,
+    thrown: Option<LoxValue>,
 }
 
 impl Backtrace {
     pub fn new(error: String) -> Backtrace {
         Backtrace {
             backtrace: Vec::new(),
+            sources: Vec::new(),
             error,
+            thrown: None,
         }
     }
 
     pub fn starting_at(error: String, error_site: (usize, usize)) -> Backtrace {
         let mut backtrace = Backtrace {
             backtrace: Vec::new(),
+            sources: Vec::new(),
+            error,
+            thrown: None,
+        };
+        backtrace.push(error_site);
+        backtrace
+    }
+
+    pub fn thrown(value: LoxValue, error_site: (usize, usize)) -> Backtrace {
+        // an uncaught error object reports its message rather than "<Error instance>"
+        let error = match &value {
+            LoxValue::Instance(instance) => {
+                match LoxInstance::get(instance, &"message".to_string()) {
+                    Some(message) => message.to_string(),
+                    None => value.to_string(),
+                }
+            }
+            _ => value.to_string(),
+        };
+        let mut backtrace = Backtrace {
+            backtrace: Vec::new(),
+            sources: Vec::new(),
             error,
+            thrown: Some(value),
         };
         backtrace.push(error_site);
         backtrace
@@ -22,13 +53,34 @@ impl Backtrace {
 
     pub fn push(&mut self, error_site: (usize, usize)) {
         self.backtrace.push(error_site);
+        self.sources.push(None);
+    }
+
+    pub fn set_source(&mut self, source: &Rc<Source>) {
+        // sites pushed since the last call belong to the code that was running
+        for site_source in self.sources.iter_mut().filter(|source| source.is_none()) {
+            *site_source = Some(Rc::clone(source));
+        }
     }
 
     pub fn get_error(&self) -> String {
         self.error.to_string()
     }
 
-    pub fn iter(&self) -> std::slice::Iter<(usize, usize)> {
+    pub fn get_thrown(&self) -> Option<LoxValue> {
+        self.thrown.clone()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (usize, usize)> {
         self.backtrace.iter()
     }
+
+    pub fn sites(
+        &self,
+    ) -> std::iter::Zip<
+        std::slice::Iter<'_, (usize, usize)>,
+        std::slice::Iter<'_, Option<Rc<Source>>>,
+    > {
+        self.backtrace.iter().zip(self.sources.iter())
+    }
 }
@@ -0,0 +1,79 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Backtrace, LoxValue, VM};
+
+pub fn install(vm: &mut VM) {
+    vm.define_native("clock", 0, |_, _| {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(time) => Ok(LoxValue::Num(time.as_secs_f64())),
+            Err(_) => Err(format!("system clock is before the Unix epoch")),
+        }
+    });
+    // print ends its line like the print statement it replaces did
+    vm.define_native("print", 1, |_, args| {
+        println!("{}", args[0]);
+        Ok(LoxValue::Nil)
+    });
+    vm.define_native("println", 1, |_, args| {
+        println!("{}", args[0]);
+        Ok(LoxValue::Nil)
+    });
+    vm.define_native("str", 1, |_, args| Ok(LoxValue::Str(args[0].to_string())));
+    vm.define_native("num", 1, |_, args| match &args[0] {
+        LoxValue::Int(num) => Ok(LoxValue::Int(*num)),
+        LoxValue::Num(num) => Ok(LoxValue::Num(*num)),
+        LoxValue::Str(str) => match str.trim().parse::<i64>() {
+            Ok(num) => Ok(LoxValue::Int(num)),
+            Err(_) => match str.trim().parse::<f64>() {
+                Ok(num) => Ok(LoxValue::Num(num)),
+                Err(_) => Err(format!("can't convert \"{str}\" to a number")),
+            },
+        },
+        value => Err(format!("can't convert {} to a number", type_name(value))),
+    });
+    vm.define_native("int", 1, |_, args| match &args[0] {
+        LoxValue::Int(num) => Ok(LoxValue::Int(*num)),
+        // floats are truncated toward zero
+        LoxValue::Num(num) => match LoxValue::Num(num.trunc()).as_int() {
+            Some(num) => Ok(LoxValue::Int(num)),
+            None => Err(format!("can't convert {} to an integer", num)),
+        },
+        value => Err(format!("can't convert {} to an integer", type_name(value))),
+    });
+    vm.define_native("len", 1, |_, args| match &args[0] {
+        LoxValue::Str(str) => Ok(LoxValue::Int(str.chars().count() as i64)),
+        LoxValue::List(list) => Ok(LoxValue::Int(list.borrow().len() as i64)),
+        LoxValue::Map(map) => Ok(LoxValue::Int(map.borrow().len() as i64)),
+        value => Err(format!("{} has no length", type_name(value))),
+    });
+    vm.define_native("Error", 1, |vm, args| {
+        Ok(vm.error_object(&Backtrace::new(args[0].to_string())))
+    });
+    vm.define_native("type", 1, |_, args| {
+        Ok(LoxValue::Str(type_name(&args[0]).to_string()))
+    });
+    // doc comments belong to declarations, so they're looked up by name
+    vm.define_native("doc", 1, |vm, args| match &args[0] {
+        LoxValue::Str(name) => match vm.curr_scope.borrow().doc(name) {
+            Some(doc) => Ok(LoxValue::Str(doc)),
+            None => Ok(LoxValue::Nil),
+        },
+        value => Err(format!("expected a name but got {}", type_name(value))),
+    });
+}
+
+fn type_name(value: &LoxValue) -> &'static str {
+    match value {
+        LoxValue::Bool(_) => "bool",
+        LoxValue::Int(_) => "int",
+        LoxValue::Num(_) => "float",
+        LoxValue::Str(_) => "string",
+        LoxValue::List(_) => "list",
+        LoxValue::Map(_) => "map",
+        LoxValue::Fn(_) | LoxValue::Native(_) => "function",
+        LoxValue::Class(_) => "class",
+        LoxValue::Instance(_) => "instance",
+        LoxValue::Module(_) => "module",
+        LoxValue::Nil => "nil",
+    }
+}
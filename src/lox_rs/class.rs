@@ -0,0 +1,66 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::{Backtrace, LoxClosure, LoxInstance, LoxValue, VM};
+
+pub struct LoxClass {
+    pub name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<String, LoxClosure>,
+}
+
+impl LoxClass {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<LoxClass>>,
+        methods: HashMap<String, LoxClosure>,
+    ) -> LoxClass {
+        LoxClass {
+            name,
+            superclass,
+            methods,
+        }
+    }
+
+    pub fn find_method(&self, name: &String) -> Option<LoxClosure> {
+        if let Some(method) = self.methods.get(name) {
+            Some(method.clone())
+        } else if let Some(superclass) = &self.superclass {
+            superclass.find_method(name)
+        } else {
+            None
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        match self.find_method(&"init".to_string()) {
+            Some(init) => init.arity(),
+            None => 0,
+        }
+    }
+
+    pub fn call(
+        class: &Rc<LoxClass>,
+        vm: &mut VM,
+        args: Vec<LoxValue>,
+    ) -> Result<LoxValue, Backtrace> {
+        let instance = LoxInstance::new(Rc::clone(class));
+        let instance = LoxValue::Instance(Rc::new(RefCell::new(instance)));
+        if let Some(init) = class.find_method(&"init".to_string()) {
+            init.bind(instance).call(vm, args)
+        } else {
+            Ok(instance)
+        }
+    }
+}
+
+impl std::fmt::Debug for LoxClass {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "<class {}>", self.name)
+    }
+}
+
+impl PartialEq for LoxClass {
+    fn eq(&self, other: &LoxClass) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
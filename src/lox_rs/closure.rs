@@ -1,10 +1,112 @@
-use super::{Backtrace, LoxValue};
+use std::{cell::RefCell, rc::Rc};
 
-#[derive(Debug, PartialEq, Copy, Clone)]
-pub struct LoxClosure {}
+use super::{stmt::Stmt, vm::Scope, Backtrace, LoxValue, Source, Unwind, VM};
+
+#[derive(Clone)]
+pub struct LoxClosure {
+    pub name: String,
+    params: Rc<Vec<String>>,
+    body: Rc<Vec<Box<dyn Stmt>>>,
+    scope: Rc<RefCell<Scope>>,
+    source: Rc<Source>,
+    is_init: bool,
+}
 
 impl LoxClosure {
-    pub fn call(&mut self, args: Vec<LoxValue>) -> Result<LoxValue, Backtrace> {
-        todo!();
+    pub fn new(
+        name: String,
+        params: Rc<Vec<String>>,
+        body: Rc<Vec<Box<dyn Stmt>>>,
+        scope: Rc<RefCell<Scope>>,
+        source: Rc<Source>,
+        is_init: bool,
+    ) -> LoxClosure {
+        LoxClosure {
+            name,
+            params,
+            body,
+            scope,
+            source,
+            is_init,
+        }
+    }
+
+    pub fn bind(&self, instance: LoxValue) -> LoxClosure {
+        let mut scope = Scope::new_inner(Rc::clone(&self.scope));
+        scope
+            .define("this".to_string(), instance)
+            .expect("a new scope has no constants");
+        LoxClosure {
+            scope: Rc::new(RefCell::new(scope)),
+            ..self.clone()
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.params.len()
+    }
+
+    pub fn call(&self, vm: &mut VM, args: Vec<LoxValue>) -> Result<LoxValue, Backtrace> {
+        let mut scope = Scope::new_inner(Rc::clone(&self.scope));
+        for (param, arg) in self.params.iter().zip(args) {
+            scope
+                .define(param.to_string(), arg)
+                .expect("a new scope has no constants");
+        }
+        let caller_scope = std::mem::replace(&mut vm.curr_scope, Rc::new(RefCell::new(scope)));
+        let caller_source = std::mem::replace(&mut vm.source, Rc::clone(&self.source));
+        let mut result = Ok(LoxValue::Nil);
+        for stmt in self.body.iter() {
+            match stmt.run(vm) {
+                Ok(()) => {}
+                Err(Unwind::Return(value)) => {
+                    result = Ok(value);
+                    break;
+                }
+                Err(Unwind::Error(mut err)) => {
+                    err.set_source(&self.source);
+                    result = Err(err);
+                    break;
+                }
+                // loops don't reach across calls, so a jump can't leave the function
+                Err(Unwind::Break(_)) => {
+                    result = Err(Backtrace::new(format!(
+                        "can't break out of function '{}'",
+                        self.name
+                    )));
+                    break;
+                }
+                Err(Unwind::Continue(_)) => {
+                    result = Err(Backtrace::new(format!(
+                        "can't continue out of function '{}'",
+                        self.name
+                    )));
+                    break;
+                }
+            }
+        }
+        vm.curr_scope = caller_scope;
+        vm.source = caller_source;
+        if self.is_init && result.is_ok() {
+            // initializers always return the instance being initialized
+            if let Some(instance) = self.scope.borrow().get(&"this".to_string()) {
+                return Ok(instance);
+            }
+        }
+        result
+    }
+}
+
+impl std::fmt::Debug for LoxClosure {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "<fn {}>", self.name)
+    }
+}
+
+impl PartialEq for LoxClosure {
+    fn eq(&self, other: &LoxClosure) -> bool {
+        // closures are compared by identity: two declarations with the same
+        // body are still different functions
+        Rc::ptr_eq(&self.body, &other.body) && Rc::ptr_eq(&self.scope, &other.scope)
     }
 }
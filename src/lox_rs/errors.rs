@@ -44,6 +44,18 @@ impl Errors<'_> {
         }
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.error_list.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        // warnings are reported but don't stop code from running
+        self.error_list.iter().any(|error| match error.severity {
+            Severity::Critical | Severity::Error => true,
+            Severity::Warning | Severity::Info => false,
+        })
+    }
+
     pub fn get_flag(&self) -> bool {
         self.flag
     }
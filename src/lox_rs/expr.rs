@@ -1,7 +1,9 @@
 pub mod printer;
 pub mod vm;
 
-use super::{Backtrace, LoxValue, TokenType};
+use std::rc::Rc;
+
+use super::{pattern::Destructure, stmt::Stmt, Backtrace, LoxValue, TokenType};
 
 // impl Visitor<String> for AstPrinter: print;
 use printer::AstPrinter;
@@ -13,15 +15,32 @@ use super::VM;
 // attr start: usize;
 // attr len: usize;
 // cast: &VarGet;
+// cast: &Get;
+// cast: &IndexGet;
+// cast: &List;
+// cast: Get;
+// cast: IndexGet;
+// cast: List;
 
 // type Binary: left: Box<dyn Expr>, oper: TokenType, right: Box<dyn Expr>;
 // type Call: callee: Box<dyn Expr>, args: Vec<Box<dyn Expr>>;
+// type Conditional: expr: Box<dyn Expr>, expr_true: Box<dyn Expr>, expr_false: Box<dyn Expr>;
+// type DestructureSet: target: Destructure, expr: Box<dyn Expr>;
+// type Get: object: Box<dyn Expr>, name: String;
 // type Grouping: expr: Box<dyn Expr>;
+// type IndexGet: object: Box<dyn Expr>, index: Box<dyn Expr>;
+// type IndexSet: object: Box<dyn Expr>, index: Box<dyn Expr>, oper: Option<TokenType>, postfix: bool, expr: Box<dyn Expr>;
+// type Interpolation: parts: Vec<Box<dyn Expr>>;
+// type Lambda: params: Rc<Vec<String>>, body: Rc<Vec<Box<dyn Stmt>>>;
+// type List: items: Vec<Box<dyn Expr>>;
 // type Literal: value: LoxValue;
+// type Map: keys: Vec<Box<dyn Expr>>, values: Vec<Box<dyn Expr>>;
 // type Logical: left: Box<dyn Expr>, oper: TokenType, right: Box<dyn Expr>;
+// type Set: object: Box<dyn Expr>, name: String, oper: Option<TokenType>, postfix: bool, expr: Box<dyn Expr>;
+// type Super: method: String;
 // type Unary: oper: TokenType, expr: Box<dyn Expr>;
 // type VarGet: name: String;
-// type VarSet: name: String, expr: Box<dyn Expr>;
+// type VarSet: name: String, oper: Option<TokenType>, postfix: bool, expr: Box<dyn Expr>;
 
 // autogenerated code
 
@@ -29,7 +48,13 @@ pub trait Expr
 {
     fn start(&self) -> usize;
     fn len(&self) -> usize;
+    fn to_get(self: Box<Self>) -> Option<Get>;
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet>;
+    fn to_list(self: Box<Self>) -> Option<List>;
     fn as_varget(&self) -> Option<&VarGet>;
+    fn as_get(&self) -> Option<&Get>;
+    fn as_indexget(&self) -> Option<&IndexGet>;
+    fn as_list(&self) -> Option<&List>;
 
     fn print(&self, print: &mut AstPrinter) -> String;
     fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>;
@@ -39,9 +64,20 @@ trait Visitor<I>
 {
     fn visit_binary(&mut self, expr: &Binary) -> I;
     fn visit_call(&mut self, expr: &Call) -> I;
+    fn visit_conditional(&mut self, expr: &Conditional) -> I;
+    fn visit_destructureset(&mut self, expr: &DestructureSet) -> I;
+    fn visit_get(&mut self, expr: &Get) -> I;
     fn visit_grouping(&mut self, expr: &Grouping) -> I;
+    fn visit_indexget(&mut self, expr: &IndexGet) -> I;
+    fn visit_indexset(&mut self, expr: &IndexSet) -> I;
+    fn visit_interpolation(&mut self, expr: &Interpolation) -> I;
+    fn visit_lambda(&mut self, expr: &Lambda) -> I;
+    fn visit_list(&mut self, expr: &List) -> I;
     fn visit_literal(&mut self, expr: &Literal) -> I;
+    fn visit_map(&mut self, expr: &Map) -> I;
     fn visit_logical(&mut self, expr: &Logical) -> I;
+    fn visit_set(&mut self, expr: &Set) -> I;
+    fn visit_super(&mut self, expr: &Super) -> I;
     fn visit_unary(&mut self, expr: &Unary) -> I;
     fn visit_varget(&mut self, expr: &VarGet) -> I;
     fn visit_varset(&mut self, expr: &VarSet) -> I;
@@ -60,7 +96,13 @@ impl Expr for Binary
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_binary(self) }
@@ -80,7 +122,13 @@ impl Expr for Call
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_call(self) }
@@ -88,6 +136,85 @@ impl Expr for Call
     { run.visit_call(self) }
 }
 
+pub struct Conditional
+{
+    pub start: usize,
+    pub len: usize,
+    pub expr: Box<dyn Expr>,
+    pub expr_true: Box<dyn Expr>,
+    pub expr_false: Box<dyn Expr>
+}
+
+impl Expr for Conditional
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_conditional(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_conditional(self) }
+}
+
+pub struct DestructureSet
+{
+    pub start: usize,
+    pub len: usize,
+    pub target: Destructure,
+    pub expr: Box<dyn Expr>
+}
+
+impl Expr for DestructureSet
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_destructureset(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_destructureset(self) }
+}
+
+pub struct Get
+{
+    pub start: usize,
+    pub len: usize,
+    pub object: Box<dyn Expr>,
+    pub name: String
+}
+
+impl Expr for Get
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { Some(*self) }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { Some(self) }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_get(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_get(self) }
+}
+
 pub struct Grouping
 {
     pub start: usize,
@@ -99,7 +226,13 @@ impl Expr for Grouping
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_grouping(self) }
@@ -107,6 +240,137 @@ impl Expr for Grouping
     { run.visit_grouping(self) }
 }
 
+pub struct IndexGet
+{
+    pub start: usize,
+    pub len: usize,
+    pub object: Box<dyn Expr>,
+    pub index: Box<dyn Expr>
+}
+
+impl Expr for IndexGet
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { Some(*self) }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { Some(self) }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_indexget(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_indexget(self) }
+}
+
+pub struct IndexSet
+{
+    pub start: usize,
+    pub len: usize,
+    pub object: Box<dyn Expr>,
+    pub index: Box<dyn Expr>,
+    pub oper: Option<TokenType>,
+    pub postfix: bool,
+    pub expr: Box<dyn Expr>
+}
+
+impl Expr for IndexSet
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_indexset(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_indexset(self) }
+}
+
+pub struct Interpolation
+{
+    pub start: usize,
+    pub len: usize,
+    pub parts: Vec<Box<dyn Expr>>
+}
+
+impl Expr for Interpolation
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_interpolation(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_interpolation(self) }
+}
+
+pub struct Lambda
+{
+    pub start: usize,
+    pub len: usize,
+    pub params: Rc<Vec<String>>,
+    pub body: Rc<Vec<Box<dyn Stmt>>>
+}
+
+impl Expr for Lambda
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_lambda(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_lambda(self) }
+}
+
+pub struct List
+{
+    pub start: usize,
+    pub len: usize,
+    pub items: Vec<Box<dyn Expr>>
+}
+
+impl Expr for List
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { Some(*self) }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { Some(self) }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_list(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_list(self) }
+}
+
 pub struct Literal
 {
     pub start: usize,
@@ -118,7 +382,13 @@ impl Expr for Literal
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_literal(self) }
@@ -126,6 +396,32 @@ impl Expr for Literal
     { run.visit_literal(self) }
 }
 
+pub struct Map
+{
+    pub start: usize,
+    pub len: usize,
+    pub keys: Vec<Box<dyn Expr>>,
+    pub values: Vec<Box<dyn Expr>>
+}
+
+impl Expr for Map
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_map(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_map(self) }
+}
+
 pub struct Logical
 {
     pub start: usize,
@@ -139,7 +435,13 @@ impl Expr for Logical
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_logical(self) }
@@ -147,6 +449,60 @@ impl Expr for Logical
     { run.visit_logical(self) }
 }
 
+pub struct Set
+{
+    pub start: usize,
+    pub len: usize,
+    pub object: Box<dyn Expr>,
+    pub name: String,
+    pub oper: Option<TokenType>,
+    pub postfix: bool,
+    pub expr: Box<dyn Expr>
+}
+
+impl Expr for Set
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_set(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_set(self) }
+}
+
+pub struct Super
+{
+    pub start: usize,
+    pub len: usize,
+    pub method: String
+}
+
+impl Expr for Super
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
+    fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
+
+    fn print(&self, print: &mut AstPrinter) -> String
+    { print.visit_super(self) }
+    fn run(&self, run: &mut VM) -> Result<LoxValue, Backtrace>
+    { run.visit_super(self) }
+}
+
 pub struct Unary
 {
     pub start: usize,
@@ -159,7 +515,13 @@ impl Expr for Unary
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_unary(self) }
@@ -178,7 +540,13 @@ impl Expr for VarGet
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { Some(self) }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_varget(self) }
@@ -191,6 +559,8 @@ pub struct VarSet
     pub start: usize,
     pub len: usize,
     pub name: String,
+    pub oper: Option<TokenType>,
+    pub postfix: bool,
     pub expr: Box<dyn Expr>
 }
 
@@ -198,7 +568,13 @@ impl Expr for VarSet
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
+    fn to_get(self: Box<Self>) -> Option<Get> { None }
+    fn to_indexget(self: Box<Self>) -> Option<IndexGet> { None }
+    fn to_list(self: Box<Self>) -> Option<List> { None }
     fn as_varget(&self) -> Option<&VarGet> { None }
+    fn as_get(&self) -> Option<&Get> { None }
+    fn as_indexget(&self) -> Option<&IndexGet> { None }
+    fn as_list(&self) -> Option<&List> { None }
 
     fn print(&self, print: &mut AstPrinter) -> String
     { print.visit_varset(self) }
@@ -15,6 +15,14 @@ impl Visitor<String> for AstPrinter {
             TokenType::Star => "*",
             TokenType::Slash => "/",
             TokenType::Percent => "%",
+            TokenType::TildeSlash => "~/",
+            TokenType::StarStar => "**",
+            TokenType::Ampersand => "&",
+            TokenType::Pipe => "|",
+            TokenType::Caret => "^",
+            TokenType::LessLess => "<<",
+            TokenType::GreaterGreater => ">>",
+            TokenType::GreaterGreaterGreater => ">>>",
             _ => panic!(),
         };
         format!(
@@ -36,10 +44,71 @@ impl Visitor<String> for AstPrinter {
         )
     }
 
+    fn visit_conditional(&mut self, expr: &Conditional) -> String {
+        format!(
+            "(? {} {} {})",
+            expr.expr.print(self),
+            expr.expr_true.print(self),
+            expr.expr_false.print(self)
+        )
+    }
+
+    fn visit_destructureset(&mut self, expr: &DestructureSet) -> String {
+        format!("(set {} {})", expr.target, expr.expr.print(self))
+    }
+
+    fn visit_get(&mut self, expr: &Get) -> String {
+        format!("(get {} .{})", expr.object.print(self), expr.name)
+    }
+
     fn visit_grouping(&mut self, expr: &Grouping) -> String {
         format!("(group {})", expr.expr.print(self))
     }
 
+    fn visit_indexget(&mut self, expr: &IndexGet) -> String {
+        format!(
+            "(get {} [{}])",
+            expr.object.print(self),
+            expr.index.print(self)
+        )
+    }
+
+    fn visit_indexset(&mut self, expr: &IndexSet) -> String {
+        format!(
+            "({} {} [{}] {})",
+            set_oper(expr.oper, expr.postfix),
+            expr.object.print(self),
+            expr.index.print(self),
+            expr.expr.print(self)
+        )
+    }
+
+    fn visit_interpolation(&mut self, expr: &Interpolation) -> String {
+        format!(
+            "(interpolate {})",
+            expr.parts
+                .iter()
+                .map(|x| x.print(self))
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+
+    fn visit_lambda(&mut self, expr: &Lambda) -> String {
+        format!("(fn ({}))", expr.params.join(" "))
+    }
+
+    fn visit_list(&mut self, expr: &List) -> String {
+        format!(
+            "(list {})",
+            expr.items
+                .iter()
+                .map(|x| x.print(self))
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+
     fn visit_literal(&mut self, expr: &Literal) -> String {
         format!("{:?}", expr.value)
     }
@@ -57,10 +126,37 @@ impl Visitor<String> for AstPrinter {
         )
     }
 
+    fn visit_map(&mut self, expr: &Map) -> String {
+        format!(
+            "(map {})",
+            expr.keys
+                .iter()
+                .zip(expr.values.iter())
+                .map(|(k, v)| format!("{} {}", k.print(self), v.print(self)))
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+
+    fn visit_set(&mut self, expr: &Set) -> String {
+        format!(
+            "({} {} .{} {})",
+            set_oper(expr.oper, expr.postfix),
+            expr.object.print(self),
+            expr.name,
+            expr.expr.print(self)
+        )
+    }
+
+    fn visit_super(&mut self, expr: &Super) -> String {
+        format!("(super .{})", expr.method)
+    }
+
     fn visit_unary(&mut self, expr: &Unary) -> String {
         let oper = match expr.oper {
             TokenType::Bang => "!",
             TokenType::Minus => "-",
+            TokenType::Tilde => "~",
             _ => panic!(),
         };
         format!("({oper} {})", expr.expr.print(self))
@@ -71,6 +167,29 @@ impl Visitor<String> for AstPrinter {
     }
 
     fn visit_varset(&mut self, expr: &VarSet) -> String {
-        format!("(set {} {})", expr.name, expr.expr.print(self))
+        format!(
+            "({} {} {})",
+            set_oper(expr.oper, expr.postfix),
+            expr.name,
+            expr.expr.print(self)
+        )
+    }
+}
+
+fn set_oper(oper: Option<TokenType>, postfix: bool) -> String {
+    // plain assignment prints as set, compound assignment as its operator
+    let oper = match oper {
+        Some(TokenType::Plus) => "+=",
+        Some(TokenType::Minus) => "-=",
+        Some(TokenType::Star) => "*=",
+        Some(TokenType::Slash) => "/=",
+        Some(TokenType::Percent) => "%=",
+        Some(_) => panic!(),
+        None => "set",
+    };
+    if postfix {
+        format!("post{oper}")
+    } else {
+        oper.to_string()
     }
 }
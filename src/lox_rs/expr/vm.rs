@@ -1,106 +1,21 @@
+use std::cell::RefCell;
+
 use super::super::Backtrace;
 use super::super::VM;
+use super::super::{LoxClass, LoxClosure, LoxInstance, LoxMap, MapKey};
 use super::*;
 
 impl Visitor<Result<LoxValue, Backtrace>> for VM {
     fn visit_binary(&mut self, expr: &Binary) -> Result<LoxValue, Backtrace> {
         match expr.left.run(self) {
             Ok(lval) => match expr.right.run(self) {
-                Ok(rval) => match expr.oper {
-                    TokenType::EqualEqual => Ok(LoxValue::Bool(lval == rval)),
-                    TokenType::BangEqual => Ok(LoxValue::Bool(lval != rval)),
-                    TokenType::Less
-                    | TokenType::LessEqual
-                    | TokenType::Greater
-                    | TokenType::GreaterEqual => {
-                        // TODO: merge if let chaining becomes stable
-                        if let LoxValue::Str(ref lstr) = lval {
-                            if let LoxValue::Str(ref rstr) = rval {
-                                return match expr.oper {
-                                    TokenType::Less => Ok(LoxValue::Bool(lstr < rstr)),
-                                    TokenType::LessEqual => Ok(LoxValue::Bool(lstr <= rstr)),
-                                    TokenType::Greater => Ok(LoxValue::Bool(lstr > rstr)),
-                                    TokenType::GreaterEqual => Ok(LoxValue::Bool(lstr >= rstr)),
-                                    _ => panic!(),
-                                };
-                            }
-                        } else if let LoxValue::Num(ref lnum) = lval {
-                            if let LoxValue::Num(ref rnum) = rval {
-                                return match expr.oper {
-                                    TokenType::Less => Ok(LoxValue::Bool(lnum < rnum)),
-                                    TokenType::LessEqual => Ok(LoxValue::Bool(lnum <= rnum)),
-                                    TokenType::Greater => Ok(LoxValue::Bool(lnum > rnum)),
-                                    TokenType::GreaterEqual => Ok(LoxValue::Bool(lnum >= rnum)),
-                                    _ => panic!(),
-                                };
-                            }
-                        }
-                        match lval {
-                            LoxValue::Str(_) | LoxValue::Num(_) => {
-                                todo!()
-                            }
-                            _ => Err(Backtrace::starting_at(
-                                format!("expected number or string"),
-                                (expr.left.start(), expr.left.len()),
-                            )),
-                        }
-                    }
-                    TokenType::Plus => {
-                        const ERR: &str = "expected two numbers or two strings";
-                        match lval {
-                            LoxValue::Num(lnum) => {
-                                if let LoxValue::Num(rnum) = rval {
-                                    Ok(LoxValue::Num(lnum + rnum))
-                                } else {
-                                    Err(Backtrace::starting_at(
-                                        ERR.to_string(),
-                                        (expr.right.start(), expr.right.len()),
-                                    ))
-                                }
-                            }
-                            LoxValue::Str(lstr) => {
-                                if let LoxValue::Str(rstr) = rval {
-                                    Ok(LoxValue::Str(format!("{}{}", lstr, rstr)))
-                                } else {
-                                    Err(Backtrace::starting_at(
-                                        ERR.to_string(),
-                                        (expr.right.start(), expr.right.len()),
-                                    ))
-                                }
-                            }
-                            _ => Err(Backtrace::starting_at(
-                                ERR.to_string(),
-                                (expr.left.start(), expr.left.len()),
-                            )),
-                        }
-                    }
-                    TokenType::Minus | TokenType::Star | TokenType::Slash | TokenType::Percent => {
-                        if let LoxValue::Num(lnum) = lval {
-                            if let LoxValue::Num(rnum) = rval {
-                                match expr.oper {
-                                    TokenType::Minus => Ok(LoxValue::Num(lnum - rnum)),
-                                    TokenType::Star => Ok(LoxValue::Num(lnum * rnum)),
-                                    TokenType::Slash => Ok(LoxValue::Num(lnum / rnum)),
-                                    TokenType::Percent => {
-                                        Ok(LoxValue::Num(lnum - (lnum / rnum).floor() * rnum))
-                                    }
-                                    _ => panic!(),
-                                }
-                            } else {
-                                Err(Backtrace::starting_at(
-                                    format!("expected two numbers"),
-                                    (expr.right.start(), expr.right.len()),
-                                ))
-                            }
-                        } else {
-                            Err(Backtrace::starting_at(
-                                format!("expected two numbers"),
-                                (expr.left.start(), expr.left.len()),
-                            ))
-                        }
-                    }
-                    _ => panic!(),
-                },
+                Ok(rval) => binary_op(
+                    expr.oper,
+                    lval,
+                    (expr.left.start(), expr.left.len()),
+                    rval,
+                    (expr.right.start(), expr.right.len()),
+                ),
                 Err(err) => Err(err),
             },
             Err(err) => Err(err),
@@ -108,17 +23,111 @@ impl Visitor<Result<LoxValue, Backtrace>> for VM {
     }
 
     fn visit_call(&mut self, expr: &Call) -> Result<LoxValue, Backtrace> {
-        let value = expr.callee.run(self);
-        if let Ok(LoxValue::Fn(mut callee)) = value {
-            match expr.args.iter().map(|arg| arg.run(self)).collect() {
-                Ok(args) => callee.call(args),
-                Err(err) => Err(err),
+        let callee = match expr.callee.run(self) {
+            Ok(callee) => callee,
+            Err(err) => return Err(err),
+        };
+        let arity = match &callee {
+            LoxValue::Fn(closure) => closure.arity(),
+            LoxValue::Native(native) => native.arity(),
+            LoxValue::Class(class) => class.arity(),
+            _ => {
+                return Err(Backtrace::starting_at(
+                    format!("expected callable"),
+                    (expr.callee.start(), expr.callee.len()),
+                ))
             }
-        } else {
-            Err(Backtrace::starting_at(
-                format!("expected callable"),
-                (expr.callee.start(), expr.callee.len()),
-            ))
+        };
+        let args: Vec<LoxValue> = match expr.args.iter().map(|arg| arg.run(self)).collect() {
+            Ok(args) => args,
+            Err(err) => return Err(err),
+        };
+        if args.len() != arity {
+            return Err(Backtrace::starting_at(
+                format!("expected {} arguments but got {}", arity, args.len()),
+                (expr.start(), expr.len()),
+            ));
+        }
+        let result = match callee {
+            LoxValue::Fn(closure) => closure.call(self, args),
+            LoxValue::Native(native) => match native.call(self, args) {
+                Ok(value) => Ok(value),
+                Err(err) => return Err(Backtrace::starting_at(err, (expr.start(), expr.len()))),
+            },
+            LoxValue::Class(class) => LoxClass::call(&class, self, args),
+            _ => {
+                return Err(Backtrace::starting_at(
+                    format!("expected callable"),
+                    (expr.callee.start(), expr.callee.len()),
+                ))
+            }
+        };
+        match result {
+            Ok(value) => Ok(value),
+            Err(mut err) => {
+                err.push((expr.start(), expr.len()));
+                Err(err)
+            }
+        }
+    }
+
+    fn visit_conditional(&mut self, expr: &Conditional) -> Result<LoxValue, Backtrace> {
+        match expr.expr.run(self) {
+            Ok(value) => {
+                if LoxValue::is_truthy(&value) {
+                    expr.expr_true.run(self)
+                } else {
+                    expr.expr_false.run(self)
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn visit_destructureset(&mut self, expr: &DestructureSet) -> Result<LoxValue, Backtrace> {
+        let value = match expr.expr.run(self) {
+            Ok(value) => value,
+            Err(err) => return Err(err),
+        };
+        let mut bindings = Vec::new();
+        if let Err(err) = expr.target.bind(value.clone(), &mut bindings) {
+            return Err(err);
+        }
+        for (name, span, value) in bindings {
+            if let Err(msg) = self.curr_scope.borrow_mut().set(name, value) {
+                return Err(Backtrace::starting_at(msg, span));
+            }
+        }
+        Ok(value)
+    }
+
+    fn visit_get(&mut self, expr: &Get) -> Result<LoxValue, Backtrace> {
+        match expr.object.run(self) {
+            Ok(LoxValue::Instance(instance)) => {
+                if let Some(value) = LoxInstance::get(&instance, &expr.name) {
+                    Ok(value)
+                } else {
+                    Err(Backtrace::starting_at(
+                        format!("undefined property '{}'", expr.name),
+                        (expr.start(), expr.len()),
+                    ))
+                }
+            }
+            Ok(LoxValue::Module(module)) => {
+                if let Some(value) = module.get(&expr.name) {
+                    Ok(value)
+                } else {
+                    Err(Backtrace::starting_at(
+                        format!("module '{}' has no member '{}'", module.name, expr.name),
+                        (expr.start(), expr.len()),
+                    ))
+                }
+            }
+            Ok(_) => Err(Backtrace::starting_at(
+                format!("only instances have properties"),
+                (expr.object.start(), expr.object.len()),
+            )),
+            Err(err) => Err(err),
         }
     }
 
@@ -126,16 +135,105 @@ impl Visitor<Result<LoxValue, Backtrace>> for VM {
         expr.expr.run(self)
     }
 
-    fn visit_literal(&mut self, expr: &Literal) -> Result<LoxValue, Backtrace> {
-        match &expr.value {
-            LoxValue::Bool(value) => Ok(LoxValue::Bool(*value)),
-            LoxValue::Num(value) => Ok(LoxValue::Num(*value)),
-            LoxValue::Str(value) => Ok(LoxValue::Str(value.to_string())),
-            LoxValue::Fn(value) => Ok(LoxValue::Fn(*value)),
-            LoxValue::Nil => Ok(LoxValue::Nil),
+    fn visit_indexget(&mut self, expr: &IndexGet) -> Result<LoxValue, Backtrace> {
+        let object = match expr.object.run(self) {
+            Ok(object) => object,
+            Err(err) => return Err(err),
+        };
+        match expr.index.run(self) {
+            Ok(index) => index_get(&object, &index, expr.object.as_ref(), expr.index.as_ref()),
+            Err(err) => Err(err),
         }
     }
 
+    fn visit_indexset(&mut self, expr: &IndexSet) -> Result<LoxValue, Backtrace> {
+        let object = match expr.object.run(self) {
+            Ok(object) => object,
+            Err(err) => return Err(err),
+        };
+        let index = match expr.index.run(self) {
+            Ok(index) => index,
+            Err(err) => return Err(err),
+        };
+        let current = match expr.oper {
+            Some(_) => {
+                match index_get(&object, &index, expr.object.as_ref(), expr.index.as_ref()) {
+                    Ok(current) => Some(current),
+                    Err(err) => return Err(err),
+                }
+            }
+            None => None,
+        };
+        let (value, result) = match assigned_value(
+            self,
+            current,
+            expr.oper,
+            expr.postfix,
+            (expr.start(), expr.len()),
+            expr.expr.as_ref(),
+        ) {
+            Ok(values) => values,
+            Err(err) => return Err(err),
+        };
+        match object {
+            LoxValue::List(list) => {
+                let mut list = list.borrow_mut();
+                match list_index(&index, list.len(), expr.index.as_ref()) {
+                    Ok(index) => {
+                        list[index] = value;
+                        Ok(result)
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+            LoxValue::Map(map) => match map_key(&index, expr.index.as_ref()) {
+                Ok(key) => {
+                    map.borrow_mut().insert(key, value);
+                    Ok(result)
+                }
+                Err(err) => Err(err),
+            },
+            _ => Err(Backtrace::starting_at(
+                format!("only lists and maps can be indexed"),
+                (expr.object.start(), expr.object.len()),
+            )),
+        }
+    }
+
+    fn visit_interpolation(&mut self, expr: &Interpolation) -> Result<LoxValue, Backtrace> {
+        let mut str = String::new();
+        for part in &expr.parts {
+            match part.run(self) {
+                Ok(LoxValue::Str(value)) => str.push_str(&value),
+                Ok(value) => str.push_str(&format!("{}", value)),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(LoxValue::Str(str))
+    }
+
+    fn visit_lambda(&mut self, expr: &Lambda) -> Result<LoxValue, Backtrace> {
+        Ok(LoxValue::Fn(LoxClosure::new(
+            "lambda".to_string(),
+            Rc::clone(&expr.params),
+            Rc::clone(&expr.body),
+            Rc::clone(&self.curr_scope),
+            Rc::clone(&self.source),
+            false,
+        )))
+    }
+
+    fn visit_list(&mut self, expr: &List) -> Result<LoxValue, Backtrace> {
+        match expr.items.iter().map(|item| item.run(self)).collect() {
+            Ok(items) => Ok(LoxValue::List(Rc::new(RefCell::new(items)))),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn visit_literal(&mut self, expr: &Literal) -> Result<LoxValue, Backtrace> {
+        Ok(expr.value.clone())
+    }
+
     fn visit_logical(&mut self, expr: &Logical) -> Result<LoxValue, Backtrace> {
         match expr.left.run(self) {
             Ok(lval) => {
@@ -154,6 +252,84 @@ impl Visitor<Result<LoxValue, Backtrace>> for VM {
         }
     }
 
+    fn visit_map(&mut self, expr: &Map) -> Result<LoxValue, Backtrace> {
+        let mut map = LoxMap::new();
+        for (key_expr, value_expr) in expr.keys.iter().zip(expr.values.iter()) {
+            let key = match key_expr.run(self) {
+                Ok(key) => match map_key(&key, key_expr.as_ref()) {
+                    Ok(key) => key,
+                    Err(err) => return Err(err),
+                },
+                Err(err) => return Err(err),
+            };
+            match value_expr.run(self) {
+                Ok(value) => map.insert(key, value),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(LoxValue::Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_set(&mut self, expr: &Set) -> Result<LoxValue, Backtrace> {
+        let instance = match expr.object.run(self) {
+            Ok(LoxValue::Instance(instance)) => instance,
+            Ok(_) => {
+                return Err(Backtrace::starting_at(
+                    format!("only instances have fields"),
+                    (expr.object.start(), expr.object.len()),
+                ))
+            }
+            Err(err) => return Err(err),
+        };
+        let current = match expr.oper {
+            Some(_) => match LoxInstance::get(&instance, &expr.name) {
+                Some(current) => Some(current),
+                None => {
+                    return Err(Backtrace::starting_at(
+                        format!("undefined property '{}'", expr.name),
+                        (expr.start(), expr.len()),
+                    ))
+                }
+            },
+            None => None,
+        };
+        match assigned_value(
+            self,
+            current,
+            expr.oper,
+            expr.postfix,
+            (expr.start(), expr.len()),
+            expr.expr.as_ref(),
+        ) {
+            Ok((value, result)) => {
+                instance.borrow_mut().set(expr.name.to_string(), value);
+                Ok(result)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn visit_super(&mut self, expr: &Super) -> Result<LoxValue, Backtrace> {
+        let superclass = self.curr_scope.borrow().get(&"super".to_string());
+        let instance = self.curr_scope.borrow().get(&"this".to_string());
+        if let (Some(LoxValue::Class(superclass)), Some(instance)) = (superclass, instance) {
+            if let Some(method) = superclass.find_method(&expr.method) {
+                Ok(LoxValue::Fn(method.bind(instance)))
+            } else {
+                Err(Backtrace::starting_at(
+                    format!("undefined property '{}'", expr.method),
+                    (expr.start(), expr.len()),
+                ))
+            }
+        } else {
+            // only reachable if the parser already reported a misplaced 'super'
+            Err(Backtrace::starting_at(
+                format!("can't use 'super' outside of a subclass"),
+                (expr.start(), expr.len()),
+            ))
+        }
+    }
+
     fn visit_unary(&mut self, expr: &Unary) -> Result<LoxValue, Backtrace> {
         match expr.oper {
             TokenType::Bang => match expr.expr.run(self) {
@@ -161,16 +337,22 @@ impl Visitor<Result<LoxValue, Backtrace>> for VM {
                 Err(err) => Err(err),
             },
             TokenType::Minus => match expr.expr.run(self) {
-                Ok(value) => {
-                    if let LoxValue::Num(num) = value {
-                        Ok(LoxValue::Num(-num))
-                    } else {
-                        Err(Backtrace::starting_at(
-                            format!("expected number"),
-                            (expr.expr.start(), expr.expr.len()),
-                        ))
-                    }
-                }
+                Ok(LoxValue::Int(num)) => checked(num.checked_neg(), (expr.start(), expr.len())),
+                Ok(LoxValue::Num(num)) => Ok(LoxValue::Num(-num)),
+                Ok(_) => Err(Backtrace::starting_at(
+                    format!("expected number"),
+                    (expr.expr.start(), expr.expr.len()),
+                )),
+                Err(err) => Err(err),
+            },
+            TokenType::Tilde => match expr.expr.run(self) {
+                Ok(value) => match value.as_int() {
+                    Some(num) => Ok(LoxValue::Int(!num)),
+                    None => Err(Backtrace::starting_at(
+                        format!("expected integer"),
+                        (expr.expr.start(), expr.expr.len()),
+                    )),
+                },
                 Err(err) => Err(err),
             },
             _ => panic!(),
@@ -178,14 +360,8 @@ impl Visitor<Result<LoxValue, Backtrace>> for VM {
     }
 
     fn visit_varget(&mut self, expr: &VarGet) -> Result<LoxValue, Backtrace> {
-        if let Some(value) = self.curr_scope.get(&expr.name) {
-            match value {
-                LoxValue::Bool(value) => Ok(LoxValue::Bool(*value)),
-                LoxValue::Num(value) => Ok(LoxValue::Num(*value)),
-                LoxValue::Str(value) => Ok(LoxValue::Str(value.to_string())),
-                LoxValue::Fn(value) => Ok(LoxValue::Fn(*value)),
-                LoxValue::Nil => Ok(LoxValue::Nil),
-            }
+        if let Some(value) = self.curr_scope.borrow().get(&expr.name) {
+            Ok(value)
         } else {
             Err(Backtrace::starting_at(
                 format!("undefined variable"),
@@ -195,27 +371,334 @@ impl Visitor<Result<LoxValue, Backtrace>> for VM {
     }
 
     fn visit_varset(&mut self, expr: &VarSet) -> Result<LoxValue, Backtrace> {
-        match expr.expr.run(self) {
-            Ok(value) => {
-                if self.curr_scope.set(
-                    expr.name.to_string(),
-                    match value {
-                        LoxValue::Bool(value) => LoxValue::Bool(value),
-                        LoxValue::Num(value) => LoxValue::Num(value),
-                        LoxValue::Str(ref value) => LoxValue::Str(value.to_string()),
-                        LoxValue::Fn(value) => LoxValue::Fn(value),
-                        LoxValue::Nil => LoxValue::Nil,
-                    },
-                ) {
-                    Ok(value)
-                } else {
-                    Err(Backtrace::starting_at(
+        let current = match expr.oper {
+            Some(_) => match self.curr_scope.borrow().get(&expr.name) {
+                Some(current) => Some(current),
+                None => {
+                    return Err(Backtrace::starting_at(
                         format!("undefined variable"),
                         (expr.start(), expr.len()),
                     ))
                 }
+            },
+            None => None,
+        };
+        match assigned_value(
+            self,
+            current,
+            expr.oper,
+            expr.postfix,
+            (expr.start(), expr.len()),
+            expr.expr.as_ref(),
+        ) {
+            Ok((value, result)) => {
+                match self
+                    .curr_scope
+                    .borrow_mut()
+                    .set(expr.name.to_string(), value)
+                {
+                    Ok(()) => Ok(result),
+                    Err(msg) => Err(Backtrace::starting_at(msg, (expr.start(), expr.len()))),
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn binary_op(
+    oper: TokenType,
+    lval: LoxValue,
+    left: (usize, usize),
+    rval: LoxValue,
+    right: (usize, usize),
+) -> Result<LoxValue, Backtrace> {
+    // shared by binary expressions and compound assignment, with the operand spans used for errors
+    let span = (left.0, right.0 + right.1 - left.0);
+    match oper {
+        TokenType::EqualEqual => Ok(LoxValue::Bool(lval == rval)),
+        TokenType::BangEqual => Ok(LoxValue::Bool(lval != rval)),
+        TokenType::Less | TokenType::LessEqual | TokenType::Greater | TokenType::GreaterEqual => {
+            // TODO: merge if let chaining becomes stable
+            if let LoxValue::Str(ref lstr) = lval {
+                if let LoxValue::Str(ref rstr) = rval {
+                    return match oper {
+                        TokenType::Less => Ok(LoxValue::Bool(lstr < rstr)),
+                        TokenType::LessEqual => Ok(LoxValue::Bool(lstr <= rstr)),
+                        TokenType::Greater => Ok(LoxValue::Bool(lstr > rstr)),
+                        TokenType::GreaterEqual => Ok(LoxValue::Bool(lstr >= rstr)),
+                        _ => panic!(),
+                    };
+                }
+            } else if let (LoxValue::Int(lnum), LoxValue::Int(rnum)) = (&lval, &rval) {
+                return match oper {
+                    TokenType::Less => Ok(LoxValue::Bool(lnum < rnum)),
+                    TokenType::LessEqual => Ok(LoxValue::Bool(lnum <= rnum)),
+                    TokenType::Greater => Ok(LoxValue::Bool(lnum > rnum)),
+                    TokenType::GreaterEqual => Ok(LoxValue::Bool(lnum >= rnum)),
+                    _ => panic!(),
+                };
+            } else if let (Some(lnum), Some(rnum)) = (lval.as_float(), rval.as_float()) {
+                return match oper {
+                    TokenType::Less => Ok(LoxValue::Bool(lnum < rnum)),
+                    TokenType::LessEqual => Ok(LoxValue::Bool(lnum <= rnum)),
+                    TokenType::Greater => Ok(LoxValue::Bool(lnum > rnum)),
+                    TokenType::GreaterEqual => Ok(LoxValue::Bool(lnum >= rnum)),
+                    _ => panic!(),
+                };
+            }
+            match lval {
+                LoxValue::Str(_) | LoxValue::Int(_) | LoxValue::Num(_) => Err(
+                    Backtrace::starting_at(format!("expected number or string"), right),
+                ),
+                _ => Err(Backtrace::starting_at(
+                    format!("expected number or string"),
+                    left,
+                )),
+            }
+        }
+        TokenType::Plus => {
+            const ERR: &str = "expected two numbers or two strings";
+            match (lval, rval) {
+                (LoxValue::Int(lnum), LoxValue::Int(rnum)) => checked(lnum.checked_add(rnum), span),
+                (LoxValue::Str(lstr), LoxValue::Str(rstr)) => {
+                    Ok(LoxValue::Str(format!("{}{}", lstr, rstr)))
+                }
+                (lval, rval) => match (lval.as_float(), rval.as_float()) {
+                    (Some(lnum), Some(rnum)) => Ok(LoxValue::Num(lnum + rnum)),
+                    _ => match lval {
+                        LoxValue::Str(_) | LoxValue::Int(_) | LoxValue::Num(_) => {
+                            Err(Backtrace::starting_at(ERR.to_string(), right))
+                        }
+                        _ => Err(Backtrace::starting_at(ERR.to_string(), left)),
+                    },
+                },
+            }
+        }
+        TokenType::Minus
+        | TokenType::Star
+        | TokenType::Slash
+        | TokenType::Percent
+        | TokenType::TildeSlash => {
+            if lval.as_float().is_none() {
+                return Err(Backtrace::starting_at(
+                    format!("expected two numbers"),
+                    left,
+                ));
+            } else if rval.as_float().is_none() {
+                return Err(Backtrace::starting_at(
+                    format!("expected two numbers"),
+                    right,
+                ));
+            }
+            if let (LoxValue::Int(lnum), LoxValue::Int(rnum)) = (&lval, &rval) {
+                let (lnum, rnum) = (*lnum, *rnum);
+                if rnum == 0 && (oper == TokenType::Percent || oper == TokenType::TildeSlash) {
+                    return Err(Backtrace::starting_at(format!("division by zero"), right));
+                }
+                match oper {
+                    TokenType::Minus => return checked(lnum.checked_sub(rnum), span),
+                    TokenType::Star => return checked(lnum.checked_mul(rnum), span),
+                    // both round toward negative infinity, so the remainder takes the sign of the divisor
+                    // anything mod -1 is 0, even the i64::MIN whose quotient overflows
+                    TokenType::Percent if rnum == -1 => return Ok(LoxValue::Int(0)),
+                    TokenType::Percent => {
+                        return checked(
+                            lnum.checked_rem(rnum).map(|rem| {
+                                if rem != 0 && (rem < 0) != (rnum < 0) {
+                                    rem + rnum
+                                } else {
+                                    rem
+                                }
+                            }),
+                            span,
+                        )
+                    }
+                    TokenType::TildeSlash => {
+                        return checked(
+                            lnum.checked_div(rnum).map(|quot| {
+                                if lnum % rnum != 0 && (lnum < 0) != (rnum < 0) {
+                                    quot - 1
+                                } else {
+                                    quot
+                                }
+                            }),
+                            span,
+                        )
+                    }
+                    // dividing integers with / always gives a float
+                    _ => {}
+                }
+            }
+            let (lnum, rnum) = (lval.as_float().unwrap(), rval.as_float().unwrap());
+            match oper {
+                TokenType::Minus => Ok(LoxValue::Num(lnum - rnum)),
+                TokenType::Star => Ok(LoxValue::Num(lnum * rnum)),
+                TokenType::Slash => Ok(LoxValue::Num(lnum / rnum)),
+                TokenType::Percent => Ok(LoxValue::Num(lnum - (lnum / rnum).floor() * rnum)),
+                TokenType::TildeSlash => Ok(LoxValue::Num((lnum / rnum).floor())),
+                _ => panic!(),
+            }
+        }
+        TokenType::StarStar => match (&lval, &rval) {
+            (LoxValue::Int(lnum), LoxValue::Int(rnum)) if *rnum >= 0 => {
+                let (lnum, rnum) = (*lnum, *rnum);
+                match u32::try_from(rnum) {
+                    Ok(rnum) => checked(lnum.checked_pow(rnum), span),
+                    // only 0, 1 and -1 have powers this large that fit
+                    Err(_) => match lnum {
+                        0 | 1 => Ok(LoxValue::Int(lnum)),
+                        -1 => Ok(LoxValue::Int(if rnum % 2 == 0 { 1 } else { -1 })),
+                        _ => checked(None, span),
+                    },
+                }
+            }
+            // a negative integer exponent gives a fraction, so the result is a float
+            _ => match (lval.as_float(), rval.as_float()) {
+                (Some(lnum), Some(rnum)) => Ok(LoxValue::Num(lnum.powf(rnum))),
+                (None, _) => Err(Backtrace::starting_at(
+                    format!("expected two numbers"),
+                    left,
+                )),
+                (_, None) => Err(Backtrace::starting_at(
+                    format!("expected two numbers"),
+                    right,
+                )),
+            },
+        },
+        TokenType::Ampersand
+        | TokenType::Pipe
+        | TokenType::Caret
+        | TokenType::LessLess
+        | TokenType::GreaterGreater
+        | TokenType::GreaterGreaterGreater => {
+            // bitwise operators take integers, including floats with an integer value
+            let lnum = match lval.as_int() {
+                Some(lnum) => lnum,
+                None => return Err(Backtrace::starting_at(format!("expected integer"), left)),
+            };
+            let rnum = match rval.as_int() {
+                Some(rnum) => rnum,
+                None => return Err(Backtrace::starting_at(format!("expected integer"), right)),
+            };
+            match oper {
+                TokenType::Ampersand => Ok(LoxValue::Int(lnum & rnum)),
+                TokenType::Pipe => Ok(LoxValue::Int(lnum | rnum)),
+                TokenType::Caret => Ok(LoxValue::Int(lnum ^ rnum)),
+                _ if rnum < 0 || rnum > 63 => Err(Backtrace::starting_at(
+                    format!("shift amount must be from 0 to 63"),
+                    right,
+                )),
+                TokenType::LessLess => Ok(LoxValue::Int(lnum << rnum)),
+                TokenType::GreaterGreater => Ok(LoxValue::Int(lnum >> rnum)),
+                // >>> shifts in zeros instead of copies of the sign bit
+                TokenType::GreaterGreaterGreater => {
+                    Ok(LoxValue::Int(((lnum as u64) >> rnum) as i64))
+                }
+                _ => panic!(),
             }
+        }
+        _ => panic!(),
+    }
+}
+
+fn checked(value: Option<i64>, span: (usize, usize)) -> Result<LoxValue, Backtrace> {
+    match value {
+        Some(value) => Ok(LoxValue::Int(value)),
+        None => Err(Backtrace::starting_at(format!("integer overflow"), span)),
+    }
+}
+
+fn assigned_value(
+    vm: &mut VM,
+    current: Option<LoxValue>,
+    oper: Option<TokenType>,
+    postfix: bool,
+    target: (usize, usize),
+    value: &dyn Expr,
+) -> Result<(LoxValue, LoxValue), Backtrace> {
+    // returns the value to store and the value of the assignment expression itself
+    let rval = match value.run(vm) {
+        Ok(rval) => rval,
+        Err(err) => return Err(err),
+    };
+    match (current, oper) {
+        (Some(lval), Some(oper)) => {
+            match binary_op(
+                oper,
+                lval.clone(),
+                target,
+                rval,
+                (value.start(), value.len()),
+            ) {
+                Ok(stored) if postfix => Ok((stored, lval)),
+                Ok(stored) => Ok((stored.clone(), stored)),
+                Err(err) => Err(err),
+            }
+        }
+        _ => Ok((rval.clone(), rval)),
+    }
+}
+
+fn index_get(
+    object: &LoxValue,
+    index: &LoxValue,
+    object_expr: &dyn Expr,
+    index_expr: &dyn Expr,
+) -> Result<LoxValue, Backtrace> {
+    match object {
+        LoxValue::List(list) => {
+            let list = list.borrow();
+            match list_index(index, list.len(), index_expr) {
+                Ok(index) => Ok(list[index].clone()),
+                Err(err) => Err(err),
+            }
+        }
+        LoxValue::Map(map) => match map_key(index, index_expr) {
+            Ok(key) => match map.borrow().get(&key) {
+                Some(value) => Ok(value),
+                None => Err(Backtrace::starting_at(
+                    format!("key not found"),
+                    (index_expr.start(), index_expr.len()),
+                )),
+            },
             Err(err) => Err(err),
+        },
+        _ => Err(Backtrace::starting_at(
+            format!("only lists and maps can be indexed"),
+            (object_expr.start(), object_expr.len()),
+        )),
+    }
+}
+
+fn list_index(index: &LoxValue, len: usize, expr: &dyn Expr) -> Result<usize, Backtrace> {
+    // resolves a possibly negative index, counting back from the end of the list
+    let index = match index.as_int() {
+        Some(index) => index,
+        None => {
+            return Err(Backtrace::starting_at(
+                format!("expected integer index"),
+                (expr.start(), expr.len()),
+            ))
         }
+    };
+    let resolved = if index < 0 { index + len as i64 } else { index };
+    if resolved < 0 || resolved >= len as i64 {
+        Err(Backtrace::starting_at(
+            format!("index {} out of range for list of length {}", index, len),
+            (expr.start(), expr.len()),
+        ))
+    } else {
+        Ok(resolved as usize)
+    }
+}
+
+fn map_key(key: &LoxValue, expr: &dyn Expr) -> Result<MapKey, Backtrace> {
+    match MapKey::from_value(key) {
+        Some(key) => Ok(key),
+        None => Err(Backtrace::starting_at(
+            format!("unhashable map key"),
+            (expr.start(), expr.len()),
+        )),
     }
 }
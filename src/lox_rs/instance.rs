@@ -0,0 +1,47 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::{LoxClass, LoxValue};
+
+pub struct LoxInstance {
+    pub class: Rc<LoxClass>,
+    fields: HashMap<String, LoxValue>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> LoxInstance {
+        LoxInstance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn get(instance: &Rc<RefCell<LoxInstance>>, name: &String) -> Option<LoxValue> {
+        // fields shadow methods, and methods are bound to the instance they were read from
+        if let Some(value) = instance.borrow().fields.get(name) {
+            Some(value.clone())
+        } else if let Some(method) = instance.borrow().class.find_method(name) {
+            Some(LoxValue::Fn(
+                method.bind(LoxValue::Instance(Rc::clone(instance))),
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, name: String, value: LoxValue) {
+        self.fields.insert(name, value);
+    }
+}
+
+impl std::fmt::Debug for LoxInstance {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "<{} instance>", self.class.name)
+    }
+}
+
+impl PartialEq for LoxInstance {
+    fn eq(&self, other: &LoxInstance) -> bool {
+        // instances are compared by identity, not by their fields
+        std::ptr::eq(self, other)
+    }
+}
@@ -3,6 +3,7 @@ use super::{
     LoxValue, NPeekable, NPeekableExt, Token, TokenType,
 };
 use std::{collections::HashMap, str::CharIndices};
+use unicode_xid::UnicodeXID;
 
 pub struct Lexer<'a> {
     source: &'a str,
@@ -10,6 +11,9 @@ pub struct Lexer<'a> {
     index: usize,
     token_start: usize,
     errors: Errors<'a>,
+    interpolations: Vec<usize>,
+    doc: Option<String>,
+    doc_start: usize,
 }
 
 impl<'a> Iterator for Lexer<'a> {
@@ -25,6 +29,7 @@ impl<'a> Iterator for Lexer<'a> {
                         continue;
                     }
                     if kind == TokenType::EOF {
+                        self.stray_doc();
                         None
                     } else {
                         Some(Token {
@@ -33,10 +38,14 @@ impl<'a> Iterator for Lexer<'a> {
                             text: Self::split_range(self.source, self.token_start, self.index)
                                 .to_string(),
                             value,
+                            doc: self.doc.take(),
                         })
                     }
                 }
-                None => None,
+                None => {
+                    self.stray_doc();
+                    None
+                }
             };
         }
     }
@@ -50,6 +59,9 @@ impl Lexer<'_> {
             index: 0,
             token_start: 0,
             errors: Errors::new(source),
+            interpolations: Vec::new(),
+            doc: None,
+            doc_start: 0,
         }
     }
 
@@ -57,10 +69,23 @@ impl Lexer<'_> {
         self.errors.coalesce(target);
     }
 
+    fn stray_doc(&mut self) {
+        // a doc comment at the end of the source has no declaration to go with
+        if let Some(_) = self.doc.take() {
+            self.errors.push(
+                "doc comment isn't followed by a declaration",
+                Severity::Warning,
+                self.doc_start,
+                3,
+                false,
+            );
+        }
+    }
+
     fn advance(&mut self) -> Option<char> {
         match self.iter.next() {
             Some((index, char)) => {
-                self.index = index + 1;
+                self.index = index + char.len_utf8();
                 Some(char)
             }
             None => None,
@@ -107,19 +132,87 @@ impl Lexer<'_> {
     }
 
     fn read_token(&mut self, char: char) -> (TokenType, LoxValue) {
-        self.token_start = self.index - 1;
+        self.token_start = self.index - char.len_utf8();
         let kind = match char {
             '(' => TokenType::LeftParen,
             ')' => TokenType::RightParen,
-            '{' => TokenType::LeftBrace,
-            '}' => TokenType::RightBrace,
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+                TokenType::LeftBrace
+            }
+            '}' => match self.interpolations.last_mut() {
+                // closes an interpolated expression, so the string continues
+                Some(0) => {
+                    self.interpolations.pop();
+                    TokenType::InterpolationEnd
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    TokenType::RightBrace
+                }
+                None => TokenType::RightBrace,
+            },
+            '[' => TokenType::LeftBracket,
+            ']' => TokenType::RightBracket,
+            ':' => TokenType::Colon,
             ',' => TokenType::Comma,
-            '.' => TokenType::Dot,
-            '-' => TokenType::Minus,
-            '+' => TokenType::Plus,
+            '.' => {
+                if self.peek() == Some('.') && self.peek_next() == Some('.') {
+                    self.advance();
+                    self.advance();
+                    TokenType::Ellipsis
+                } else {
+                    TokenType::Dot
+                }
+            }
+            '-' => {
+                if self.check('=') {
+                    TokenType::MinusEqual
+                } else if self.check('-') {
+                    TokenType::MinusMinus
+                } else {
+                    TokenType::Minus
+                }
+            }
+            '&' => TokenType::Ampersand,
+            '^' => TokenType::Caret,
+            '|' => TokenType::Pipe,
+            '+' => {
+                if self.check('=') {
+                    TokenType::PlusEqual
+                } else if self.check('+') {
+                    TokenType::PlusPlus
+                } else {
+                    TokenType::Plus
+                }
+            }
+            '?' => TokenType::Question,
             ';' => TokenType::Semicolon,
-            '*' => TokenType::Star,
-            '%' => TokenType::Percent,
+            '*' => {
+                if self.check('=') {
+                    TokenType::StarEqual
+                } else if self.check('*') {
+                    TokenType::StarStar
+                } else {
+                    TokenType::Star
+                }
+            }
+            '~' => {
+                if self.check('/') {
+                    TokenType::TildeSlash
+                } else {
+                    TokenType::Tilde
+                }
+            }
+            '%' => {
+                if self.check('=') {
+                    TokenType::PercentEqual
+                } else {
+                    TokenType::Percent
+                }
+            }
             '!' => {
                 if self.check('=') {
                     TokenType::BangEqual
@@ -130,6 +223,8 @@ impl Lexer<'_> {
             '=' => {
                 if self.check('=') {
                     TokenType::EqualEqual
+                } else if self.check('>') {
+                    TokenType::FatArrow
                 } else {
                     TokenType::Equal
                 }
@@ -137,6 +232,8 @@ impl Lexer<'_> {
             '<' => {
                 if self.check('=') {
                     TokenType::LessEqual
+                } else if self.check('<') {
+                    TokenType::LessLess
                 } else {
                     TokenType::Less
                 }
@@ -144,24 +241,70 @@ impl Lexer<'_> {
             '>' => {
                 if self.check('=') {
                     TokenType::GreaterEqual
+                } else if self.check('>') {
+                    if self.check('>') {
+                        TokenType::GreaterGreaterGreater
+                    } else {
+                        TokenType::GreaterGreater
+                    }
                 } else {
                     TokenType::Greater
                 }
             }
+            '"' if self.peek() == Some('"') && self.peek_next() == Some('"') => {
+                self.advance();
+                self.advance();
+                return self.text_block();
+            }
             '"' => TokenType::String,
             '0'..='9' => TokenType::Number,
-            'A'..='Z' | 'a'..='z' | '_' => TokenType::Identifier,
+            'r' if self.peek() == Some('"') || self.peek() == Some('#') => {
+                return self.raw_string()
+            }
+            c if c == '_' || c.is_xid_start() => TokenType::Identifier,
             '/' => {
                 if self.check('/') {
+                    // '///' starts a doc comment, but '////' is a plain comment again
+                    let is_doc = self.peek() == Some('/') && self.peek_next() != Some('/');
+                    let start = self.index + 1;
+                    let mut end = self.source.len();
                     while let Some(c) = self.advance() {
                         if c == '\n' {
+                            end = self.index - 1;
                             break;
                         }
                     }
+                    if is_doc {
+                        if let None = self.doc {
+                            self.doc_start = self.token_start;
+                        }
+                        let line = Self::split_range(self.source, start, end);
+                        let line = line.strip_prefix(' ').unwrap_or(line).trim_end();
+                        self.doc = match self.doc.take() {
+                            Some(doc) => Some(format!("{doc}\n{line}")),
+                            None => Some(line.to_string()),
+                        };
+                    }
                     match self.advance_past_whitespace() {
                         Some(c) => return self.read_token(c),
                         None => TokenType::EOF,
                     }
+                } else if self.check('*') {
+                    if !self.block_comment() {
+                        self.errors.push(
+                            "unterminated block comment",
+                            Severity::Error,
+                            self.token_start,
+                            2,
+                            false,
+                        );
+                    }
+                    match self.advance_past_whitespace() {
+                        Some(c) => return self.read_token(c),
+                        None => TokenType::EOF,
+                    }
+                } else if self.check('=') {
+                    TokenType::SlashEqual
                 } else {
                     TokenType::Slash
                 }
@@ -171,21 +314,60 @@ impl Lexer<'_> {
                     "unexpected character",
                     Severity::Error,
                     self.token_start,
-                    1,
+                    char.len_utf8(),
                     false,
                 );
                 TokenType::Error
             }
         };
         match kind {
-            TokenType::String => {
-                if let Some(str) = self.string() {
-                    (TokenType::Literal, LoxValue::Str(str))
-                } else {
+            TokenType::String | TokenType::InterpolationEnd => match self.string() {
+                Some((str, true)) => {
+                    self.interpolations.push(0);
+                    if kind == TokenType::String {
+                        (TokenType::InterpolationStart, LoxValue::Str(str))
+                    } else {
+                        (TokenType::InterpolationMid, LoxValue::Str(str))
+                    }
+                }
+                Some((str, false)) => {
+                    if kind == TokenType::String {
+                        (TokenType::Literal, LoxValue::Str(str))
+                    } else {
+                        (TokenType::InterpolationEnd, LoxValue::Str(str))
+                    }
+                }
+                None => {
+                    self.errors.push(
+                        "unterminated string",
+                        Severity::Error,
+                        self.token_start,
+                        self.index - self.token_start,
+                        false,
+                    );
                     (TokenType::Error, LoxValue::Nil)
                 }
-            }
-            TokenType::Number => (TokenType::Literal, LoxValue::Num(self.number())),
+            },
+            TokenType::Number => match self.number() {
+                Ok(value) => (TokenType::Literal, value),
+                Err(msg) => {
+                    self.errors.push(
+                        msg,
+                        Severity::Error,
+                        self.token_start,
+                        self.index - self.token_start,
+                        false,
+                    );
+                    // still emit a literal so the parser doesn't report the gap as well
+                    let text = Self::split_range(self.source, self.token_start, self.index);
+                    let prefixed = matches!(text.get(..2), Some("0x" | "0o" | "0b"));
+                    if !prefixed && text.contains(['.', 'e', 'E']) {
+                        (TokenType::Literal, LoxValue::Num(0.0))
+                    } else {
+                        (TokenType::Literal, LoxValue::Int(0))
+                    }
+                }
+            },
             TokenType::Identifier => match self.identifier() {
                 TokenType::True => (TokenType::Literal, LoxValue::Bool(true)),
                 TokenType::False => (TokenType::Literal, LoxValue::Bool(false)),
@@ -196,55 +378,336 @@ impl Lexer<'_> {
         }
     }
 
-    fn string(&mut self) -> Option<String> {
-        let mut str = String::new();
-        let mut escaped = false;
+    /// block_comment: skip the rest of a block comment, including any nested in it.
+    /// Returns whether the comment was closed before the end of the source.
+    fn block_comment(&mut self) -> bool {
+        let mut depth = 1;
         while let Some(char) = self.advance() {
             match char {
-                c if escaped == true => {
-                    match c {
-                        'r' => str.push('\r'),
-                        'n' => str.push('\n'),
-                        't' => str.push('\t'),
-                        _ => str.push(c),
+                '/' if self.check('*') => depth += 1,
+                '*' if self.check('/') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return true;
                     }
-                    escaped = false;
                 }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// string: read the rest of a string literal or string part.
+    /// Returns the text and whether it ended at an interpolation opener instead of a quote.
+    fn string(&mut self) -> Option<(String, bool)> {
+        let start = self.index;
+        let mut escaped = false;
+        while let Some(char) = self.advance() {
+            match char {
+                _ if escaped == true => escaped = false,
                 '\\' => escaped = true,
-                '"' => return Some(str),
-                c => str.push(c),
+                '"' => return Some((self.unescape(start, self.index - 1), false)),
+                '$' if self.check('{') => {
+                    return Some((self.unescape(start, self.index - 2), true))
+                }
+                _ => {}
             }
         }
         None
     }
 
-    fn number(&mut self) -> f64 {
-        self.integer();
+    /// text_block: read the rest of a triple-quoted string.
+    /// A line break right after the opening quotes and a last line holding only the
+    /// closing quotes are left out, and the indentation the other lines share is removed.
+    /// Escapes work as in other strings, but interpolation doesn't.
+    fn text_block(&mut self) -> (TokenType, LoxValue) {
+        let start = self.index;
+        let mut escaped = false;
+        let end = loop {
+            match self.advance() {
+                Some(_) if escaped == true => escaped = false,
+                Some('\\') => escaped = true,
+                Some('"') if self.peek() == Some('"') && self.peek_next() == Some('"') => {
+                    let end = self.index - 1;
+                    self.advance();
+                    self.advance();
+                    break end;
+                }
+                Some(_) => {}
+                None => {
+                    self.errors.push(
+                        "unterminated string",
+                        Severity::Error,
+                        self.token_start,
+                        self.index - self.token_start,
+                        false,
+                    );
+                    return (TokenType::Error, LoxValue::Nil);
+                }
+            }
+        };
+
+        // each line is kept with where it starts, so escapes are read from the source;
+        // trailing whitespace is dropped from every line, including one right after the quotes
+        let mut lines = Vec::new();
+        let mut line_start = start;
+        for line in Self::split_range(self.source, start, end).split('\n') {
+            lines.push((line_start, line.trim_end()));
+            line_start += line.len() + 1;
+        }
+        if lines.len() > 1 && lines[0].1.trim().is_empty() {
+            lines.remove(0);
+        }
+        if lines.len() > 1 && lines[lines.len() - 1].1.trim().is_empty() {
+            lines.pop();
+        }
+        let mut indent: Option<&str> = None;
+        for (_, line) in lines.iter().filter(|(_, line)| !line.trim().is_empty()) {
+            let line_indent = &line[..line.len() - line.trim_start().len()];
+            indent = match indent {
+                Some(indent) => {
+                    // compared by character so the slice never splits multibyte whitespace
+                    let shared = indent
+                        .char_indices()
+                        .zip(line_indent.chars())
+                        .find(|((_, a), b)| a != b)
+                        .map_or(indent.len().min(line_indent.len()), |((index, _), _)| index);
+                    Some(&indent[..shared])
+                }
+                None => Some(line_indent),
+            };
+        }
+        let indent = indent.map_or(0, str::len);
+        let str = lines
+            .iter()
+            .map(|(line_start, line)| {
+                if line.trim().is_empty() {
+                    String::new()
+                } else {
+                    self.unescape(line_start + indent, line_start + line.len())
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        (TokenType::Literal, LoxValue::Str(str))
+    }
+
+    /// raw_string: read the rest of a raw string after its r.
+    /// Raw strings have no escapes or interpolation, and end at a quote followed
+    /// by as many #s as came before the opening quote.
+    fn raw_string(&mut self) -> (TokenType, LoxValue) {
+        let mut hashes = 0;
+        while self.check('#') {
+            hashes += 1;
+        }
+        if !self.check('"') {
+            self.errors.push(
+                "expected \" to start raw string",
+                Severity::Error,
+                self.token_start,
+                self.index - self.token_start,
+                false,
+            );
+            return (TokenType::Error, LoxValue::Nil);
+        }
+        let start = self.index;
+        while let Some(char) = self.advance() {
+            if char == '"' {
+                let end = self.index - 1;
+                let mut closing = 0;
+                while closing < hashes && self.check('#') {
+                    closing += 1;
+                }
+                if closing == hashes {
+                    let str = Self::split_range(self.source, start, end).to_string();
+                    return (TokenType::Literal, LoxValue::Str(str));
+                }
+            }
+        }
+        self.errors.push(
+            "unterminated raw string",
+            Severity::Error,
+            self.token_start,
+            self.index - self.token_start,
+            false,
+        );
+        (TokenType::Error, LoxValue::Nil)
+    }
+
+    /// unescape: the text of a string between start and end, with its escapes replaced.
+    /// Invalid escapes are reported and left out.
+    fn unescape(&mut self, start: usize, end: usize) -> String {
+        let mut str = String::new();
+        let mut chars = Self::split_range(self.source, start, end)
+            .char_indices()
+            .peekable();
+        while let Some((index, char)) = chars.next() {
+            if char != '\\' {
+                str.push(char);
+                continue;
+            }
+            let escape_start = start + index;
+            let escaped = match chars.next() {
+                Some((_, 'n')) => Ok('\n'),
+                Some((_, 'r')) => Ok('\r'),
+                Some((_, 't')) => Ok('\t'),
+                Some((_, '0')) => Ok('\0'),
+                Some((_, c @ ('\\' | '"' | '\'' | '$'))) => Ok(c),
+                Some((_, 'x')) => {
+                    let mut digits = String::new();
+                    while digits.len() < 2 {
+                        match chars.next_if(|(_, c)| c.is_ascii_hexdigit()) {
+                            Some((_, c)) => digits.push(c),
+                            None => break,
+                        }
+                    }
+                    match u8::from_str_radix(&digits, 16) {
+                        Ok(byte) if digits.len() == 2 && byte <= 0x7f => Ok(byte as char),
+                        Ok(_) if digits.len() == 2 => Err("\\x escape must be at most 7F"),
+                        _ => Err("\\x escape needs two hex digits"),
+                    }
+                }
+                Some((_, 'u')) => {
+                    if let None = chars.next_if(|(_, c)| *c == '{') {
+                        Err("expected { after \\u")
+                    } else {
+                        let mut digits = String::new();
+                        while let Some((_, c)) = chars.next_if(|(_, c)| c.is_ascii_hexdigit()) {
+                            digits.push(c);
+                        }
+                        if let None = chars.next_if(|(_, c)| *c == '}') {
+                            Err("expected } to close \\u escape")
+                        } else if digits.is_empty() || digits.len() > 6 {
+                            Err("\\u escape needs 1 to 6 hex digits")
+                        } else {
+                            match u32::from_str_radix(&digits, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                            {
+                                Some(c) => Ok(c),
+                                None => Err("\\u escape is not a valid character"),
+                            }
+                        }
+                    }
+                }
+                _ => Err("unknown escape sequence"),
+            };
+            match escaped {
+                Ok(c) => str.push(c),
+                Err(msg) => {
+                    let escape_end = match chars.peek() {
+                        Some((index, _)) => start + index,
+                        None => end,
+                    };
+                    self.errors.push(
+                        msg,
+                        Severity::Error,
+                        escape_start,
+                        escape_end - escape_start,
+                        false,
+                    );
+                }
+            }
+        }
+        str
+    }
+
+    /// number: read the rest of a number literal.
+    /// Decimal literals without a fraction or exponent are integers, as are 0x, 0o
+    /// and 0b literals; returns why the literal is malformed if it is.
+    fn number(&mut self) -> Result<LoxValue, &'static str> {
+        let radix = match (self.source.as_bytes()[self.token_start], self.peek()) {
+            (b'0', Some('x')) => 16,
+            (b'0', Some('o')) => 8,
+            (b'0', Some('b')) => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.advance();
+            let count = self.digits(radix, 0);
+            if let Some(c) = self.peek() {
+                if c.is_ascii_alphanumeric() {
+                    self.digits(36, 1).ok();
+                    return Err(match radix {
+                        16 => "invalid digit in hex literal",
+                        8 => "invalid digit in octal literal",
+                        _ => "invalid digit in binary literal",
+                    });
+                }
+            }
+            return match count {
+                Ok(0) => Err(match radix {
+                    16 => "expected digits after 0x",
+                    8 => "expected digits after 0o",
+                    _ => "expected digits after 0b",
+                }),
+                Ok(_) => {
+                    let text = Self::split_range(self.source, self.token_start + 2, self.index);
+                    match i64::from_str_radix(&text.replace('_', ""), radix) {
+                        Ok(value) => Ok(LoxValue::Int(value)),
+                        Err(_) => Err("integer literal too large"),
+                    }
+                }
+                Err(msg) => Err(msg),
+            };
+        }
+
+        let mut float = false;
+        let mut result = self.digits(10, 1);
         if self.peek() == Some('.') && (Some('0')..=Some('9')).contains(&self.peek_next()) {
             self.advance();
-            self.integer();
+            result = result.and(self.digits(10, 0));
+            float = true;
         }
-        let peek = self.peek();
-        if peek == Some('E')
-            || peek == Some('e') && (Some('0')..=Some('9')).contains(&self.peek_next())
-        {
+        if let Some('e' | 'E') = self.peek() {
             self.advance();
-            self.integer();
+            if let Some('+' | '-') = self.peek() {
+                self.advance();
+            }
+            result = match (result, self.digits(10, 0)) {
+                (Ok(_), Ok(0)) => Err("expected digits in exponent"),
+                (result, exponent) => result.and(exponent),
+            };
+            float = true;
         }
-        let text = Self::split_range(self.source, self.token_start, self.index);
-        if let Ok(num) = text.parse::<f64>() {
-            num
+        if let Err(msg) = result {
+            return Err(msg);
+        }
+        let text = Self::split_range(self.source, self.token_start, self.index).replace('_', "");
+        if float {
+            match text.parse::<f64>() {
+                Ok(num) => Ok(LoxValue::Num(num)),
+                Err(_) => Err("malformed number literal"),
+            }
         } else {
-            f64::NAN
+            match text.parse::<i64>() {
+                Ok(num) => Ok(LoxValue::Int(num)),
+                Err(_) => Err("integer literal too large"),
+            }
         }
     }
 
-    fn integer(&mut self) {
+    /// digits: read a run of digits in the given radix, allowing _ between digits.
+    /// count is how many digits of the run were already read; returns how many there are.
+    fn digits(&mut self, radix: u32, mut count: usize) -> Result<usize, &'static str> {
+        let mut misplaced = false;
+        let mut separator = false;
         while let Some(char) = self.peek() {
-            match char {
-                '0'..='9' => self.advance(),
-                _ => break,
-            };
+            if char == '_' {
+                misplaced |= count == 0 || separator;
+                separator = true;
+            } else if char.is_digit(radix) {
+                count += 1;
+                separator = false;
+            } else {
+                break;
+            }
+            self.advance();
+        }
+        if misplaced || separator {
+            Err("digit separators must be between digits")
+        } else {
+            Ok(count)
         }
     }
 
@@ -253,27 +716,35 @@ impl Lexer<'_> {
         // https://github.com/rust-lang/rust/issues/88674
         let lexer_keywords: HashMap<&str, TokenType> = HashMap::from([
             ("and", TokenType::And),
+            ("as", TokenType::As),
+            ("break", TokenType::Break),
+            ("catch", TokenType::Catch),
             ("class", TokenType::Class),
+            ("const", TokenType::Const),
+            ("continue", TokenType::Continue),
             ("else", TokenType::Else),
             ("false", TokenType::False),
+            ("finally", TokenType::Finally),
             ("for", TokenType::For),
             ("fn", TokenType::Fn),
             ("if", TokenType::If),
+            ("import", TokenType::Import),
+            ("match", TokenType::Match),
             ("nil", TokenType::Nil),
             ("or", TokenType::Or),
-            ("print", TokenType::Print), // TODO: remove once functions work
             ("return", TokenType::Return),
             ("super", TokenType::Super),
             ("this", TokenType::This),
+            ("throw", TokenType::Throw),
             ("true", TokenType::True),
+            ("try", TokenType::Try),
             ("var", TokenType::Var),
             ("while", TokenType::While),
         ]);
 
         while let Some(char) = self.peek() {
-            match char {
-                'A'..='Z' | 'a'..='z' | '0'..='9' | '_' => {}
-                _ => break,
+            if !char.is_xid_continue() {
+                break;
             }
             self.advance();
         }
@@ -294,3 +765,128 @@ impl Lexer<'_> {
         post
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// lex: the tokens of source, and whether the lexer reported an error.
+    fn lex(source: &str) -> (Vec<Token>, bool) {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.by_ref().collect();
+        (tokens, lexer.errors.has_errors())
+    }
+
+    fn literal(source: &str) -> (LoxValue, bool) {
+        let (tokens, errors) = lex(source);
+        assert_eq!(tokens.len(), 1, "{source} should be a single token");
+        assert!(tokens[0].kind == TokenType::Literal);
+        (tokens[0].value.clone(), errors)
+    }
+
+    #[test]
+    fn valid_numbers() {
+        assert_eq!(literal("42"), (LoxValue::Int(42), false));
+        assert_eq!(literal("1_000_000"), (LoxValue::Int(1_000_000), false));
+        assert_eq!(literal("0xff"), (LoxValue::Int(255), false));
+        assert_eq!(literal("0xDead_Beef"), (LoxValue::Int(0xdead_beef), false));
+        assert_eq!(literal("0o17"), (LoxValue::Int(15), false));
+        assert_eq!(literal("0b1010_1010"), (LoxValue::Int(170), false));
+        assert_eq!(literal("2.5"), (LoxValue::Num(2.5), false));
+        assert_eq!(literal("1e3"), (LoxValue::Num(1000.0), false));
+        assert_eq!(literal("1.5E-2"), (LoxValue::Num(0.015), false));
+        assert_eq!(literal("2e1_0"), (LoxValue::Num(2e10), false));
+    }
+
+    #[test]
+    fn malformed_numbers_are_placeholder_literals() {
+        assert_eq!(literal("0x"), (LoxValue::Int(0), true));
+        assert_eq!(literal("0o"), (LoxValue::Int(0), true));
+        assert_eq!(literal("0b"), (LoxValue::Int(0), true));
+        assert_eq!(literal("0xfg"), (LoxValue::Int(0), true));
+        assert_eq!(literal("0o8"), (LoxValue::Int(0), true));
+        assert_eq!(literal("0b102"), (LoxValue::Int(0), true));
+        assert_eq!(literal("0x_1"), (LoxValue::Int(0), true));
+        assert_eq!(literal("1_"), (LoxValue::Int(0), true));
+        assert_eq!(literal("1__0"), (LoxValue::Int(0), true));
+        assert_eq!(literal("99999999999999999999"), (LoxValue::Int(0), true));
+        assert_eq!(literal("1e"), (LoxValue::Num(0.0), true));
+        assert_eq!(literal("1e+"), (LoxValue::Num(0.0), true));
+        assert_eq!(literal("1.5_"), (LoxValue::Num(0.0), true));
+    }
+
+    fn string(value: &str) -> LoxValue {
+        LoxValue::Str(value.to_string())
+    }
+
+    #[test]
+    fn valid_escapes() {
+        assert_eq!(literal(r#""a\nb\tc\r""#), (string("a\nb\tc\r"), false));
+        assert_eq!(literal(r#""\0\\\"\'\$""#), (string("\0\\\"'$"), false));
+        assert_eq!(literal(r#""\x41\x7f\x7F""#), (string("A\x7f\x7f"), false));
+        assert_eq!(
+            literal(r#""\u{41}\u{e9}\u{1F600}""#),
+            (string("Aé😀"), false)
+        );
+        assert_eq!(
+            literal(r#""\u{10FFFF}\u{0000e9}""#),
+            (string("\u{10ffff}é"), false)
+        );
+    }
+
+    #[test]
+    fn out_of_range_escapes() {
+        for source in [
+            r#""\x80""#,
+            r#""\xff""#,
+            r#""\x4""#,
+            r#""\xg0""#,
+            r#""\u{D800}""#,
+            r#""\u{DFFF}""#,
+            r#""\u{110000}""#,
+            r#""\u{FFFFFFF}""#,
+            r#""\u{}""#,
+            r#""\u41""#,
+            r#""\u{41""#,
+            r#""\q""#,
+        ] {
+            let (_, errors) = literal(source);
+            assert!(errors, "{source} should be an error");
+        }
+        // the rest of the string is still read
+        assert_eq!(literal(r#""a\x80b""#), (string("ab"), true));
+    }
+
+    #[test]
+    fn identifiers() {
+        for source in ["café", "été", "_x1", "名前", "Σ_2", "x\u{301}"] {
+            let (tokens, errors) = lex(source);
+            assert!(!errors, "{source} should be an identifier");
+            assert_eq!(tokens.len(), 1, "{source} should be a single token");
+            assert!(tokens[0].kind == TokenType::Identifier);
+            assert_eq!(tokens[0].text, source);
+        }
+        // combining marks can continue an identifier but not start one
+        let (tokens, _) = lex("\u{301}x");
+        assert!(tokens.iter().all(|token| token.text != "\u{301}x"));
+    }
+
+    #[test]
+    fn text_blocks() {
+        let source = "\"\"\"\n    fn f() {\n        return 1;  \n    }\n\n  \"\"\"";
+        assert_eq!(
+            literal(source),
+            (string("fn f() {\n    return 1;\n}\n"), false)
+        );
+        // content after the opening quotes is a line like any other
+        let source = "\"\"\"first   \n\\tsecond \n\"\"\"";
+        assert_eq!(literal(source), (string("first\n\tsecond"), false));
+        let source = "\"\"\"\r\n  a\r\n    b\r\n  \"\"\"";
+        assert_eq!(literal(source), (string("a\n  b"), false));
+        // different multibyte whitespace shares no indent
+        let source = "\"\"\"\n\u{2003}a\n\u{2002}b\n\"\"\"";
+        assert_eq!(literal(source), (string("\u{2003}a\n\u{2002}b"), false));
+        let source = "\"\"\"\n\u{2003}\u{2003}a\n\u{2003}b\n\"\"\"";
+        assert_eq!(literal(source), (string("\u{2003}a\nb"), false));
+    }
+}
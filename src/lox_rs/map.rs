@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use super::LoxValue;
+
+/// MapKey: the hashable subset of LoxValue.
+/// Integral numbers are keyed as integers, so `m[1] == m[1.0]`. Other floats
+/// are keyed by their bits after folding every NaN into a single NaN, so a
+/// NaN key can be read back.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum MapKey {
+    Bool(bool),
+    Int(i64),
+    Num(u64),
+    Str(String),
+    Nil,
+}
+
+impl MapKey {
+    pub fn from_value(value: &LoxValue) -> Option<MapKey> {
+        match value {
+            LoxValue::Bool(value) => Some(MapKey::Bool(*value)),
+            LoxValue::Int(_) | LoxValue::Num(_) => Some(match value.as_int() {
+                // -0.0 is integral, so it shares the key of 0
+                Some(value) => MapKey::Int(value),
+                None => MapKey::Num(match value.as_float() {
+                    Some(value) if !value.is_nan() => value.to_bits(),
+                    _ => f64::NAN.to_bits(),
+                }),
+            }),
+            LoxValue::Str(value) => Some(MapKey::Str(value.to_string())),
+            LoxValue::Nil => Some(MapKey::Nil),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> LoxValue {
+        match self {
+            MapKey::Bool(value) => LoxValue::Bool(*value),
+            MapKey::Int(value) => LoxValue::Int(*value),
+            MapKey::Num(bits) => LoxValue::Num(f64::from_bits(*bits)),
+            MapKey::Str(value) => LoxValue::Str(value.to_string()),
+            MapKey::Nil => LoxValue::Nil,
+        }
+    }
+}
+
+/// LoxMap: a hash map that remembers insertion order, so maps print the same way every run.
+#[derive(Debug, Default)]
+pub struct LoxMap {
+    index: HashMap<MapKey, usize>,
+    entries: Vec<(LoxValue, LoxValue)>,
+}
+
+impl LoxMap {
+    pub fn new() -> LoxMap {
+        LoxMap::default()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<LoxValue> {
+        self.index.get(key).map(|i| self.entries[*i].1.clone())
+    }
+
+    pub fn insert(&mut self, key: MapKey, value: LoxValue) {
+        if let Some(i) = self.index.get(&key) {
+            self.entries[*i].1 = value;
+        } else {
+            self.index.insert(key.clone(), self.entries.len());
+            self.entries.push((key.to_value(), value));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, (LoxValue, LoxValue)> {
+        self.entries.iter()
+    }
+
+    /// eq_by: whether both maps have the same keys, with values equal by the given comparison.
+    pub fn eq_by(&self, other: &LoxMap, mut eq: impl FnMut(&LoxValue, &LoxValue) -> bool) -> bool {
+        self.len() == other.len()
+            && self
+                .index
+                .iter()
+                .all(|(key, i)| match other.index.get(key) {
+                    Some(j) => eq(&self.entries[*i].1, &other.entries[*j].1),
+                    None => false,
+                })
+    }
+}
+
+impl PartialEq for LoxMap {
+    fn eq(&self, other: &LoxMap) -> bool {
+        self.eq_by(other, |a, b| a == b)
+    }
+}
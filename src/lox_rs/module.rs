@@ -0,0 +1,44 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use super::{vm::Scope, LoxValue};
+
+/// Source: the text of a script or module.
+/// Closures keep the source they were parsed from, so errors raised in
+/// imported code are shown against the right file.
+pub struct Source {
+    pub path: Option<PathBuf>,
+    pub code: String,
+}
+
+/// LoxModule: the top-level names of an imported file.
+pub struct LoxModule {
+    pub name: String,
+    scope: Rc<RefCell<Scope>>,
+}
+
+impl LoxModule {
+    pub fn new(name: String, scope: Rc<RefCell<Scope>>) -> LoxModule {
+        LoxModule { name, scope }
+    }
+
+    pub fn get(&self, name: &String) -> Option<LoxValue> {
+        // only the module's own names are visible, not the globals it can see
+        self.scope.borrow().vars.get(name).cloned()
+    }
+
+    pub fn is_constant(&self, name: &String) -> bool {
+        self.scope.borrow().constants.contains(name)
+    }
+}
+
+impl std::fmt::Debug for LoxModule {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "<module {}>", self.name)
+    }
+}
+
+impl PartialEq for LoxModule {
+    fn eq(&self, other: &LoxModule) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
@@ -0,0 +1,38 @@
+use std::rc::Rc;
+
+use super::{LoxValue, VM};
+
+pub type NativeFn = dyn Fn(&mut VM, Vec<LoxValue>) -> Result<LoxValue, String>;
+
+#[derive(Clone)]
+pub struct LoxNative {
+    pub name: String,
+    arity: usize,
+    func: Rc<NativeFn>,
+}
+
+impl LoxNative {
+    pub fn new(name: String, arity: usize, func: Rc<NativeFn>) -> LoxNative {
+        LoxNative { name, arity, func }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn call(&self, vm: &mut VM, args: Vec<LoxValue>) -> Result<LoxValue, String> {
+        (self.func)(vm, args)
+    }
+}
+
+impl std::fmt::Debug for LoxNative {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(formatter, "<native fn {}>", self.name)
+    }
+}
+
+impl PartialEq for LoxNative {
+    fn eq(&self, other: &LoxNative) -> bool {
+        Rc::ptr_eq(&self.func, &other.func)
+    }
+}
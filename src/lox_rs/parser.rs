@@ -1,7 +1,10 @@
+use std::{collections::HashMap, rc::Rc};
+
 use super::{
     errors::{Errors, Severity},
     expr::*,
     lexer::Lexer,
+    pattern::{Destructure, Pattern},
     stmt::*,
     LoxValue, NPeekable, NPeekableExt, Token, TokenType,
 };
@@ -10,6 +13,26 @@ pub struct Parser<'a> {
     source: &'a str,
     lexer: NPeekable<Lexer<'a>>,
     errors: Errors<'a>,
+    fn_kind: FnKind,
+    class_kind: ClassKind,
+    loop_labels: Vec<Option<String>>,
+    // names declared in each enclosing scope, and whether they are constant
+    scopes: Vec<HashMap<String, bool>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FnKind {
+    None,
+    Function,
+    Method,
+    Initializer,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassKind {
+    None,
+    Class,
+    Subclass,
 }
 
 enum PatternElem {
@@ -66,6 +89,10 @@ impl Parser<'_> {
             source,
             lexer: Lexer::new(source).npeekable(),
             errors: Errors::new(source),
+            fn_kind: FnKind::None,
+            class_kind: ClassKind::None,
+            loop_labels: Vec::new(),
+            scopes: vec![HashMap::new()],
         }
     }
 
@@ -121,19 +148,32 @@ impl Parser<'_> {
                     return;
                 }
                 TokenType::Class
+                | TokenType::Const
                 | TokenType::For
                 | TokenType::Fn
                 | TokenType::If
-                | TokenType::Print
+                | TokenType::Import
+                | TokenType::Match
                 | TokenType::Return
+                | TokenType::Throw
+                | TokenType::Try
                 | TokenType::Var
                 | TokenType::While => return,
-                _ => {}
+                _ => {
+                    self.lexer.next();
+                }
             }
         }
     }
 
     fn block(&mut self) -> Option<(Vec<Box<dyn Stmt>>, (usize, usize))> {
+        self.scopes.push(HashMap::new());
+        let block = self.block_contents();
+        self.scopes.pop();
+        block
+    }
+
+    fn block_contents(&mut self) -> Option<(Vec<Box<dyn Stmt>>, (usize, usize))> {
         let mut stmts = Vec::new();
         if let Some(brace) = self
             .lexer
@@ -145,6 +185,7 @@ impl Parser<'_> {
                     match brace.kind {
                         TokenType::RightBrace => {
                             let end = brace.start + 1;
+                            self.stray_doc();
                             self.lexer.next();
                             return Some((stmts, (start, end - start)));
                         }
@@ -183,153 +224,853 @@ impl Parser<'_> {
     }
 
     fn declaration(&mut self) -> Option<Box<dyn Stmt>> {
-        if let Some(token) = self.lexer.peek() {
-            match token.kind {
-                TokenType::Var => self.var_declaration(),
-                _ => self.statement(),
+        let kind = if let Some(token) = self.lexer.peek() {
+            token.kind
+        } else {
+            return None;
+        };
+        match kind {
+            TokenType::Class => self.class_declaration(),
+            TokenType::Fn if self.at_fn_declaration() => self.fn_declaration(),
+            TokenType::Var | TokenType::Const => self.var_declaration(),
+            _ => {
+                self.stray_doc();
+                self.statement()
             }
+        }
+    }
+
+    fn at_fn_declaration(&mut self) -> bool {
+        // 'fn' followed by a name declares a function,
+        // otherwise it starts a lambda expression statement
+        let is_declaration = if let Some(token) = self.lexer.peek_next() {
+            token.kind == TokenType::Identifier
         } else {
-            None
+            false
+        };
+        self.lexer.reset_cursor();
+        is_declaration
+    }
+
+    fn stray_doc(&mut self) {
+        // only declarations keep their doc comments, so one before anything else is reported
+        if let Some(token) = self.lexer.peek_if(|token| token.doc.is_some()) {
+            let (start, len) = (token.start, token.text.len());
+            self.errors.push(
+                "doc comment isn't followed by a declaration",
+                Severity::Warning,
+                start,
+                len,
+                false,
+            );
         }
     }
 
-    fn var_declaration(&mut self) -> Option<Box<dyn Stmt>> {
+    fn var_destructuring(&mut self) -> Option<Box<dyn Stmt>> {
+        let token = if let Some(token) = self.lexer.next_if(|token| token.kind == TokenType::Var) {
+            token
+        } else {
+            return None;
+        };
+        let target = if let Some(target) = self.destructure() {
+            target
+        } else {
+            return None;
+        };
+        let (start, len) = target.span();
         match self.try_match(vec![
             (
                 Box::new(|parser| {
-                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Var))
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Equal))
                 }),
-                (true, "expected 'var'"),
+                (true, "expected = after destructuring pattern"),
+            ),
+            (
+                Box::new(|parser| pattern_expr(parser.expression())),
+                (false, "expected expression after ="),
             ),
             (
                 Box::new(|parser| {
                     pattern_token(
                         parser
                             .lexer
-                            .next_if(|token| token.kind == TokenType::Identifier),
+                            .next_if(|token| token.kind == TokenType::Semicolon),
                     )
                 }),
-                (false, "expected name after 'var'"),
+                (false, "expected ; after expression"),
             ),
         ]) {
-            Err(_) => None,
+            Err(_) => {
+                if let None = self.lexer.peek_if(|token| token.kind == TokenType::Equal) {
+                    // try_match doesn't know where the pattern ended
+                    self.errors.push(
+                        "expected = after destructuring pattern",
+                        Severity::Error,
+                        start + len,
+                        0,
+                        true,
+                    );
+                }
+                None
+            }
             Ok(mut parts) => {
-                if let Some(token) = self.lexer.next_if(|token| {
-                    token.kind == TokenType::Semicolon || token.kind == TokenType::Equal
-                }) {
-                    if token.kind == TokenType::Semicolon {
-                        Some(Box::new(VarStmt {
-                            name: parts.remove(1).as_token().text,
-                            expr: None,
-                            start: pattern_start(&parts[0]),
-                            len: token.start - pattern_start(&parts[0]) + 1,
-                        }))
+                let (start, len) = target.span();
+                for name in target.names() {
+                    if !self.declare(name, false) {
+                        self.redeclare_error(start, len);
+                    }
+                }
+                Some(Box::new(DestructureStmt {
+                    start: token.start,
+                    len: pattern_end(&parts[2]) - token.start,
+                    target,
+                    expr: parts.remove(1).as_expr(),
+                    doc: token.doc,
+                }))
+            }
+        }
+    }
+
+    fn destructure(&mut self) -> Option<Destructure> {
+        // parses a name, a [list] pattern with an optional ...rest, or a {map or object} pattern
+        if let Some(token) = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::Identifier)
+        {
+            return Some(Destructure::Name {
+                span: (token.start, token.text.len()),
+                name: token.text,
+            });
+        }
+        if let Some(bracket) = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::LeftBracket)
+        {
+            let mut items = Vec::new();
+            let mut rest = None;
+            let mut end = bracket.start + bracket.text.len();
+            loop {
+                if let Some(token) = self
+                    .lexer
+                    .next_if(|token| token.kind == TokenType::RightBracket)
+                {
+                    let end = token.start + token.text.len();
+                    return Some(Destructure::List {
+                        items,
+                        rest,
+                        span: (bracket.start, end - bracket.start),
+                    });
+                }
+                if let Some(_) = rest {
+                    self.errors.push(
+                        "expected ] after rest element",
+                        Severity::Error,
+                        end,
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+                if let Some(ellipsis) = self
+                    .lexer
+                    .next_if(|token| token.kind == TokenType::Ellipsis)
+                {
+                    if let Some(token) = self
+                        .lexer
+                        .next_if(|token| token.kind == TokenType::Identifier)
+                    {
+                        end = token.start + token.text.len();
+                        rest = Some((token.text, (ellipsis.start, end - ellipsis.start)));
                     } else {
-                        match self.try_match(vec![
-                            (
-                                Box::new(|parser| pattern_expr(parser.expression())),
-                                (false, "expected expression after ="),
-                            ),
-                            (
-                                Box::new(|parser| {
-                                    pattern_token(
-                                        parser
-                                            .lexer
-                                            .next_if(|token| token.kind == TokenType::Semicolon),
-                                    )
-                                }),
-                                (false, "expected ; after expression"),
-                            ),
-                        ]) {
-                            Err(_) => None,
-                            Ok(mut inner_parts) => Some(Box::new(VarStmt {
-                                name: parts.remove(1).as_token().text,
-                                expr: Some(inner_parts.remove(0).as_expr()),
-                                start: pattern_start(&parts[0]),
-                                len: pattern_end(&inner_parts[0]) - pattern_start(&parts[0]),
-                            })),
-                        }
+                        self.errors.push(
+                            "expected name after ...",
+                            Severity::Error,
+                            ellipsis.start + ellipsis.text.len(),
+                            0,
+                            true,
+                        );
+                        return None;
                     }
+                } else if let Some(item) = self.destructure() {
+                    end = item.span().0 + item.span().1;
+                    items.push(item);
                 } else {
+                    return None;
+                }
+                if let Some(comma) = self.lexer.next_if(|token| token.kind == TokenType::Comma) {
+                    end = comma.start + comma.text.len();
+                } else if let None = self
+                    .lexer
+                    .peek_if(|token| token.kind == TokenType::RightBracket)
+                {
                     self.errors.push(
-                        "expected = or ; after name",
+                        "expected , or ] after list pattern element",
                         Severity::Error,
-                        pattern_end(&parts[1]),
+                        end,
                         0,
                         true,
                     );
-                    None
+                    return None;
+                }
+            }
+        }
+        if let Some(brace) = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::LeftBrace)
+        {
+            let mut names = Vec::new();
+            let mut end = brace.start + brace.text.len();
+            loop {
+                if let Some(token) = self
+                    .lexer
+                    .next_if(|token| token.kind == TokenType::RightBrace)
+                {
+                    let end = token.start + token.text.len();
+                    return Some(Destructure::Object {
+                        names,
+                        span: (brace.start, end - brace.start),
+                    });
+                }
+                if let Some(token) = self
+                    .lexer
+                    .next_if(|token| token.kind == TokenType::Identifier)
+                {
+                    end = token.start + token.text.len();
+                    names.push((token.text.clone(), (token.start, token.text.len())));
+                } else {
+                    self.errors.push(
+                        "expected name in object pattern",
+                        Severity::Error,
+                        end,
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+                if let Some(comma) = self.lexer.next_if(|token| token.kind == TokenType::Comma) {
+                    end = comma.start + comma.text.len();
+                } else if let None = self
+                    .lexer
+                    .peek_if(|token| token.kind == TokenType::RightBrace)
+                {
+                    self.errors.push(
+                        "expected , or } after object pattern name",
+                        Severity::Error,
+                        end,
+                        0,
+                        true,
+                    );
+                    return None;
                 }
             }
         }
+        let end = match self.lexer.peek() {
+            Some(token) => token.start,
+            None => self.source.len(),
+        };
+        self.errors.push(
+            "expected destructuring pattern",
+            Severity::Error,
+            end,
+            0,
+            true,
+        );
+        None
     }
 
-    fn statement(&mut self) -> Option<Box<dyn Stmt>> {
-        if let Some(token) = self.lexer.peek() {
-            match token.kind {
-                TokenType::LeftBrace => self.block_statement(),
-                TokenType::For => self.for_statement(),
-                TokenType::If => self.if_statement(),
-                TokenType::Print => self.print_statement(),
-                TokenType::While => self.while_statement(),
-                _ => {
-                    let start = token.start;
-                    let len = token.text.len();
-                    if let Some(expr) = self.expr_statement() {
-                        Some(expr)
+    fn class_declaration(&mut self) -> Option<Box<dyn Stmt>> {
+        match self.try_match(vec![
+            (
+                Box::new(|parser| {
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Class))
+                }),
+                (true, "expected 'class'"),
+            ),
+            (
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Identifier),
+                    )
+                }),
+                (false, "expected name after 'class'"),
+            ),
+        ]) {
+            Err(_) => None,
+            Ok(mut parts) => {
+                let name = parts.remove(1).as_token();
+                if !self.declare(&name.text, false) {
+                    self.redeclare_error(name.start, name.text.len());
+                }
+                let mut end = name.start + name.text.len();
+                let superclass: Option<Box<dyn Expr>> =
+                    if let Some(less) = self.lexer.next_if(|token| token.kind == TokenType::Less) {
+                        if let Some(superclass) = self
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Identifier)
+                        {
+                            if superclass.text == name.text {
+                                self.errors.push(
+                                    "a class can't inherit from itself",
+                                    Severity::Error,
+                                    superclass.start,
+                                    superclass.text.len(),
+                                    false,
+                                );
+                            }
+                            end = superclass.start + superclass.text.len();
+                            Some(Box::new(VarGet {
+                                start: superclass.start,
+                                len: superclass.text.len(),
+                                name: superclass.text,
+                            }))
+                        } else {
+                            self.errors.push(
+                                "expected superclass name after <",
+                                Severity::Error,
+                                less.start + less.text.len(),
+                                0,
+                                true,
+                            );
+                            return None;
+                        }
+                    } else {
+                        None
+                    };
+                if let Some(brace) = self
+                    .lexer
+                    .next_if(|token| token.kind == TokenType::LeftBrace)
+                {
+                    let enclosing = self.class_kind;
+                    self.class_kind = if superclass.is_some() {
+                        ClassKind::Subclass
+                    } else {
+                        ClassKind::Class
+                    };
+                    let methods = self.class_body(brace.start + brace.text.len());
+                    self.class_kind = enclosing;
+                    if let Some((methods, end)) = methods {
+                        Some(Box::new(ClassStmt {
+                            start: pattern_start(&parts[0]),
+                            len: end - pattern_start(&parts[0]),
+                            name: name.text,
+                            superclass,
+                            methods,
+                            doc: pattern_doc(&parts[0]),
+                        }))
                     } else {
-                        self.errors
-                            .push("expected statement", Severity::Error, start, len, true);
-                        self.lexer.next();
                         None
                     }
+                } else {
+                    self.errors
+                        .push("expected { after class name", Severity::Error, end, 0, true);
+                    None
                 }
             }
-        } else {
-            None
         }
     }
 
-    fn block_statement(&mut self) -> Option<Box<dyn Stmt>> {
-        if let Some(block) = self.block() {
-            let (stmts, (start, len)) = block;
-            Some(Box::new(BlockStmt { stmts, start, len }))
+    fn class_body(&mut self, start: usize) -> Option<(Vec<FnStmt>, usize)> {
+        // parses methods up to and including the closing brace,
+        // returning them along with the end of the class body
+        let mut methods = Vec::new();
+        let mut end = start;
+        loop {
+            if let Some(_) = self
+                .lexer
+                .peek_if(|token| token.kind == TokenType::RightBrace)
+            {
+                self.stray_doc();
+            }
+            if let Some(brace) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::RightBrace)
+            {
+                return Some((methods, brace.start + brace.text.len()));
+            } else if let Some(_) = self
+                .lexer
+                .peek_if(|token| token.kind == TokenType::Identifier)
+            {
+                if let Some(method) = self.function(FnKind::Method) {
+                    end = method.start + method.len;
+                    methods.push(method);
+                } else {
+                    return None;
+                }
+            } else {
+                self.errors.push(
+                    "expected method or } in class body",
+                    Severity::Error,
+                    end,
+                    0,
+                    true,
+                );
+                return None;
+            }
+        }
+    }
+
+    fn fn_declaration(&mut self) -> Option<Box<dyn Stmt>> {
+        if let Some(token) = self.lexer.next_if(|token| token.kind == TokenType::Fn) {
+            if let Some(mut func) = self.function(FnKind::Function) {
+                if !self.declare(&func.name, false) {
+                    self.redeclare_error(func.start, func.name.len());
+                }
+                func.doc = token.doc;
+                Some(Box::new(func))
+            } else {
+                None
+            }
         } else {
             None
         }
     }
 
-    fn expr_statement(&mut self) -> Option<Box<dyn Stmt>> {
+    fn function(&mut self, kind: FnKind) -> Option<FnStmt> {
         match self.try_match(vec![
             (
-                Box::new(|parser| pattern_expr(parser.expression())),
-                (true, "expected expression"),
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Identifier),
+                    )
+                }),
+                (false, "expected function name"),
             ),
             (
                 Box::new(|parser| {
                     pattern_token(
                         parser
                             .lexer
-                            .next_if(|token| token.kind == TokenType::Semicolon),
+                            .next_if(|token| token.kind == TokenType::LeftParen),
                     )
                 }),
-                (false, "expected ; after expression statement"),
+                (false, "expected ( after function name"),
             ),
         ]) {
             Err(_) => None,
-            Ok(mut parts) => Some(Box::new(ExprStmt {
-                start: pattern_start(&parts[0]),
-                len: pattern_end(&parts[1]) - pattern_start(&parts[0]),
-                expr: parts.remove(0).as_expr(),
-            })),
+            Ok(mut parts) => {
+                if let Some(params) = self.parameters(pattern_end(&parts[1])) {
+                    let name = parts.remove(0).as_token();
+                    let kind = if kind == FnKind::Method && name.text == "init" {
+                        FnKind::Initializer
+                    } else {
+                        kind
+                    };
+                    if let Some((body, end)) = self.function_body(kind, &params) {
+                        Some(FnStmt {
+                            start: name.start,
+                            len: end - name.start,
+                            name: name.text,
+                            params: Rc::new(params),
+                            body: Rc::new(body),
+                            doc: name.doc,
+                        })
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    fn function_body(
+        &mut self,
+        kind: FnKind,
+        params: &Vec<String>,
+    ) -> Option<(Vec<Box<dyn Stmt>>, usize)> {
+        // parses a function body block, returning its statements and end
+        let enclosing = self.fn_kind;
+        let enclosing_loops = std::mem::take(&mut self.loop_labels);
+        self.fn_kind = kind;
+        self.scopes.push(HashMap::new());
+        for param in params {
+            self.declare(param, false);
+        }
+        let body = self.block();
+        self.scopes.pop();
+        self.fn_kind = enclosing;
+        self.loop_labels = enclosing_loops;
+        if let Some((body, (start, len))) = body {
+            Some((body, start + len))
+        } else {
+            if !self.errors.get_flag() {
+                let (start, len) = if let Some(token) = self.lexer.peek() {
+                    (token.start, token.text.len())
+                } else {
+                    (self.source.len(), 0)
+                };
+                self.errors.push(
+                    "expected block after parameters",
+                    Severity::Error,
+                    start,
+                    len,
+                    true,
+                );
+            }
+            None
+        }
+    }
+
+    fn parameters(&mut self, start: usize) -> Option<Vec<String>> {
+        // parses a parameter list whose left paren ends at start
+        let mut params = Vec::new();
+        let mut end = start;
+        if let Some(_) = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::RightParen)
+        {
+            return Some(params);
+        }
+        loop {
+            if let Some(param) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::Identifier)
+            {
+                end = param.start + param.text.len();
+                params.push(param.text);
+            } else {
+                self.errors
+                    .push("expected parameter name", Severity::Error, end, 0, true);
+                return None;
+            }
+            if let Some(token) = self.lexer.next_if(|token| match token.kind {
+                TokenType::Comma | TokenType::RightParen => true,
+                _ => false,
+            }) {
+                if token.kind == TokenType::RightParen {
+                    return Some(params);
+                }
+                end = token.start + token.text.len();
+            } else {
+                self.errors.push(
+                    "expected , or ) after parameter",
+                    Severity::Error,
+                    end,
+                    0,
+                    true,
+                );
+                return None;
+            }
         }
     }
 
-    fn for_statement(&mut self) -> Option<Box<dyn Stmt>> {
+    fn var_declaration(&mut self) -> Option<Box<dyn Stmt>> {
+        // parses 'var' declarations and 'const' ones, which must be initialized
+        let constant = if let Some(_) = self.lexer.peek_if(|token| token.kind == TokenType::Const) {
+            true
+        } else {
+            false
+        };
+        self.lexer.peek();
+        let after = self.lexer.peek_next().map(|token| token.kind);
+        self.lexer.reset_cursor();
+        if let (false, Some(TokenType::LeftBracket | TokenType::LeftBrace)) = (constant, after) {
+            return self.var_destructuring();
+        }
         match self.try_match(vec![
             (
                 Box::new(|parser| {
-                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::For))
+                    pattern_token(parser.lexer.next_if(|token| {
+                        token.kind == TokenType::Var || token.kind == TokenType::Const
+                    }))
+                }),
+                (true, "expected 'var' or 'const'"),
+            ),
+            (
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Identifier),
+                    )
+                }),
+                (
+                    false,
+                    if constant {
+                        "expected name after 'const'"
+                    } else {
+                        "expected name after 'var'"
+                    },
+                ),
+            ),
+        ]) {
+            Err(_) => None,
+            Ok(mut parts) => {
+                if let (true, None) = (
+                    constant,
+                    self.lexer.peek_if(|token| token.kind == TokenType::Equal),
+                ) {
+                    self.errors.push(
+                        "expected = after constant name",
+                        Severity::Error,
+                        pattern_end(&parts[1]),
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+                if let Some(token) = self.lexer.next_if(|token| {
+                    token.kind == TokenType::Semicolon || token.kind == TokenType::Equal
+                }) {
+                    if token.kind == TokenType::Semicolon {
+                        let name = parts.remove(1).as_token();
+                        if !self.declare(&name.text, false) {
+                            self.redeclare_error(name.start, name.text.len());
+                        }
+                        let name = name.text;
+                        Some(Box::new(VarStmt {
+                            name,
+                            expr: None,
+                            constant,
+                            doc: pattern_doc(&parts[0]),
+                            start: pattern_start(&parts[0]),
+                            len: token.start - pattern_start(&parts[0]) + 1,
+                        }))
+                    } else {
+                        match self.try_match(vec![
+                            (
+                                Box::new(|parser| pattern_expr(parser.expression())),
+                                (false, "expected expression after ="),
+                            ),
+                            (
+                                Box::new(|parser| {
+                                    pattern_token(
+                                        parser
+                                            .lexer
+                                            .next_if(|token| token.kind == TokenType::Semicolon),
+                                    )
+                                }),
+                                (false, "expected ; after expression"),
+                            ),
+                        ]) {
+                            Err(_) => None,
+                            Ok(mut inner_parts) => {
+                                // the name is declared after its initializer, which can't see it
+                                let name = parts.remove(1).as_token();
+                                if !self.declare(&name.text, constant) {
+                                    self.redeclare_error(name.start, name.text.len());
+                                }
+                                let name = name.text;
+                                Some(Box::new(VarStmt {
+                                    name,
+                                    expr: Some(inner_parts.remove(0).as_expr()),
+                                    constant,
+                                    doc: pattern_doc(&parts[0]),
+                                    start: pattern_start(&parts[0]),
+                                    len: pattern_end(&inner_parts[0]) - pattern_start(&parts[0]),
+                                }))
+                            }
+                        }
+                    }
+                } else {
+                    self.errors.push(
+                        "expected = or ; after name",
+                        Severity::Error,
+                        pattern_end(&parts[1]),
+                        0,
+                        true,
+                    );
+                    None
+                }
+            }
+        }
+    }
+
+    fn statement(&mut self) -> Option<Box<dyn Stmt>> {
+        let (kind, start, len) = if let Some(token) = self.lexer.peek() {
+            (token.kind, token.start, token.text.len())
+        } else {
+            return None;
+        };
+        match kind {
+            TokenType::LeftBrace if !self.at_map_literal() => self.block_statement(),
+            TokenType::Break | TokenType::Continue => self.jump_statement(),
+            TokenType::For => self.for_statement(None),
+            TokenType::If => self.if_statement(),
+            TokenType::Import => self.import_statement(),
+            TokenType::Match => self.match_statement(),
+            TokenType::Return => self.return_statement(),
+            TokenType::Throw => self.throw_statement(),
+            TokenType::Try => self.try_statement(),
+            TokenType::While => self.while_statement(None),
+            TokenType::Identifier if self.at_label() => self.labeled_statement(),
+            TokenType::Identifier if self.at_print_statement() => self.print_statement(),
+            _ => {
+                if let Some(expr) = self.expr_statement() {
+                    Some(expr)
+                } else {
+                    self.errors
+                        .push("expected statement", Severity::Error, start, len, true);
+                    self.lexer.next();
+                    None
+                }
+            }
+        }
+    }
+
+    fn at_map_literal(&mut self) -> bool {
+        // a left brace starts a map literal rather than a block when it is followed by
+        // a single-token key and a colon, unless the key is the label of a loop
+        let key = self.lexer.peek_next().map(|token| token.kind);
+        let colon = self.lexer.peek_next().map(|token| token.kind);
+        let after = self.lexer.peek_next().map(|token| token.kind);
+        self.lexer.reset_cursor();
+        match (key, colon, after) {
+            (
+                Some(TokenType::Identifier),
+                Some(TokenType::Colon),
+                Some(TokenType::For | TokenType::While),
+            ) => false,
+            (Some(TokenType::Literal | TokenType::Identifier), Some(TokenType::Colon), _) => true,
+            _ => false,
+        }
+    }
+
+    fn at_label(&mut self) -> bool {
+        // checks for an identifier followed by a colon
+        let is_label = if let Some(token) = self.lexer.peek_next() {
+            token.kind == TokenType::Colon
+        } else {
+            false
+        };
+        self.lexer.reset_cursor();
+        is_label
+    }
+
+    fn at_print_statement(&mut self) -> bool {
+        // checks for the old 'print' statement: print followed by an expression without
+        // parentheses, which can't be anything else when print is a function
+        let is_print = if let Some(token) = self.lexer.peek() {
+            token.text == "print"
+        } else {
+            false
+        };
+        let is_statement = if let Some(token) = self.lexer.peek_next() {
+            match token.kind {
+                TokenType::Identifier
+                | TokenType::Literal
+                | TokenType::InterpolationStart
+                | TokenType::True
+                | TokenType::False
+                | TokenType::Nil
+                | TokenType::This
+                | TokenType::Super
+                | TokenType::Bang => true,
+                _ => false,
+            }
+        } else {
+            false
+        };
+        self.lexer.reset_cursor();
+        is_print && is_statement
+    }
+
+    fn print_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        // kept so old scripts still run: print x; is read as the call print(x)
+        match self.try_match(vec![
+            (
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Identifier),
+                    )
+                }),
+                (true, "expected 'print'"),
+            ),
+            (
+                Box::new(|parser| pattern_expr(parser.expression())),
+                (false, "expected expression after 'print'"),
+            ),
+            (
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Semicolon),
+                    )
+                }),
+                (false, "expected semicolon after print statement"),
+            ),
+        ]) {
+            Err(_) => None,
+            Ok(mut parts) => {
+                let start = pattern_start(&parts[0]);
+                let end = pattern_end(&parts[2]);
+                let name = parts.remove(0).as_token();
+                let expr = parts.remove(0).as_expr();
+                self.errors.push(
+                    "the print statement is deprecated, call print(...) instead",
+                    Severity::Warning,
+                    name.start,
+                    name.text.len(),
+                    false,
+                );
+                Some(Box::new(ExprStmt {
+                    start,
+                    len: end - start,
+                    expr: Box::new(Call {
+                        start,
+                        len: expr.start() + expr.len() - start,
+                        callee: Box::new(VarGet {
+                            start: name.start,
+                            len: name.text.len(),
+                            name: name.text,
+                        }),
+                        args: vec![expr],
+                    }),
+                }))
+            }
+        }
+    }
+
+    fn block_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        if let Some(block) = self.block() {
+            let (stmts, (start, len)) = block;
+            Some(Box::new(BlockStmt { stmts, start, len }))
+        } else {
+            None
+        }
+    }
+
+    fn expr_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        match self.try_match(vec![
+            (
+                Box::new(|parser| pattern_expr(parser.expression())),
+                (true, "expected expression"),
+            ),
+            (
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Semicolon),
+                    )
+                }),
+                (false, "expected ; after expression statement"),
+            ),
+        ]) {
+            Err(_) => None,
+            Ok(mut parts) => Some(Box::new(ExprStmt {
+                start: pattern_start(&parts[0]),
+                len: pattern_end(&parts[1]) - pattern_start(&parts[0]),
+                expr: parts.remove(0).as_expr(),
+            })),
+        }
+    }
+
+    fn for_statement(&mut self, label: Option<String>) -> Option<Box<dyn Stmt>> {
+        self.loop_labels.push(label.clone());
+        self.scopes.push(HashMap::new());
+        let parts = self.try_match(vec![
+            (
+                Box::new(|parser| {
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::For))
                 }),
                 (true, "expected 'for'"),
             ),
@@ -380,7 +1121,10 @@ impl Parser<'_> {
                 Box::new(|parser| pattern_stmt(parser.block_statement())),
                 (false, "expected block after for statement"),
             ),
-        ]) {
+        ]);
+        self.scopes.pop();
+        self.loop_labels.pop();
+        match parts {
             Err(_) => None,
             Ok(mut parts) => {
                 let start = pattern_start(&parts[0]);
@@ -389,19 +1133,10 @@ impl Parser<'_> {
                     stmts: vec![
                         parts.remove(1).as_stmt(),
                         Box::new(WhileStmt {
+                            label,
                             expr: parts.remove(1).as_stmt().to_exprstmt().unwrap().expr,
-                            stmt: Box::new(BlockStmt {
-                                stmts: vec![
-                                    parts.remove(2).as_stmt(),
-                                    Box::new(ExprStmt {
-                                        expr: parts.remove(1).as_expr(),
-                                        start,
-                                        len,
-                                    }),
-                                ],
-                                start,
-                                len,
-                            }),
+                            incr: Some(parts.remove(1).as_expr()),
+                            stmt: parts.remove(1).as_stmt(),
                             start,
                             len,
                         }),
@@ -478,113 +1213,1068 @@ impl Parser<'_> {
         }
     }
 
-    fn print_statement(&mut self) -> Option<Box<dyn Stmt>> {
+    fn import_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        let token = if let Some(token) = self.lexer.next_if(|token| token.kind == TokenType::Import)
+        {
+            token
+        } else {
+            return None;
+        };
+        let (path, mut end) = if let Some(Token {
+            value: LoxValue::Str(path),
+            start,
+            text,
+            ..
+        }) = self.lexer.next_if(|token| token.kind == TokenType::Literal)
+        {
+            (path, start + text.len())
+        } else {
+            self.errors.push(
+                "expected module path after 'import'",
+                Severity::Error,
+                token.start + token.text.len(),
+                0,
+                true,
+            );
+            return None;
+        };
+        let mut name = None;
+        let mut names = Vec::new();
+        if let Some(as_token) = self.lexer.next_if(|token| token.kind == TokenType::As) {
+            if let Some(token) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::Identifier)
+            {
+                end = token.start + token.text.len();
+                name = Some(token.text);
+            } else {
+                self.errors.push(
+                    "expected module name after 'as'",
+                    Severity::Error,
+                    as_token.start + as_token.text.len(),
+                    0,
+                    true,
+                );
+                return None;
+            }
+        } else if let Some(for_token) = self.lexer.next_if(|token| token.kind == TokenType::For) {
+            end = for_token.start + for_token.text.len();
+            loop {
+                if let Some(token) = self
+                    .lexer
+                    .next_if(|token| token.kind == TokenType::Identifier)
+                {
+                    end = token.start + token.text.len();
+                    names.push(token.text);
+                } else {
+                    self.errors
+                        .push("expected name to import", Severity::Error, end, 0, true);
+                    return None;
+                }
+                if let Some(comma) = self.lexer.next_if(|token| token.kind == TokenType::Comma) {
+                    end = comma.start + comma.text.len();
+                } else {
+                    break;
+                }
+            }
+        } else {
+            self.errors.push(
+                "expected 'as' or 'for' after module path",
+                Severity::Error,
+                end,
+                0,
+                true,
+            );
+            return None;
+        }
+        if let Some(semicolon) = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::Semicolon)
+        {
+            for name in name.iter().chain(names.iter()) {
+                if !self.declare(name, false) {
+                    self.redeclare_error(token.start, semicolon.start + 1 - token.start);
+                }
+            }
+            Some(Box::new(ImportStmt {
+                start: token.start,
+                len: semicolon.start + semicolon.text.len() - token.start,
+                path,
+                name,
+                names,
+            }))
+        } else {
+            self.errors.push(
+                "expected ; after import statement",
+                Severity::Error,
+                end,
+                0,
+                true,
+            );
+            None
+        }
+    }
+
+    fn match_statement(&mut self) -> Option<Box<dyn Stmt>> {
         match self.try_match(vec![
             (
                 Box::new(|parser| {
-                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Print))
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Match))
                 }),
-                (true, "expected 'print'"),
+                (true, "expected 'match'"),
             ),
             (
                 Box::new(|parser| pattern_expr(parser.expression())),
-                (false, "expected expression after 'print'"),
+                (false, "expected value after 'match'"),
             ),
             (
                 Box::new(|parser| {
                     pattern_token(
                         parser
                             .lexer
-                            .next_if(|token| token.kind == TokenType::Semicolon),
+                            .next_if(|token| token.kind == TokenType::LeftBrace),
                     )
                 }),
-                (false, "expected semicolon after print statement"),
+                (false, "expected { after match value"),
             ),
         ]) {
             Err(_) => None,
-            Ok(mut parts) => Some(Box::new(PrintStmt {
-                expr: parts.remove(1).as_expr(),
-                start: pattern_start(&parts[0]),
-                len: pattern_end(&parts[1]) - pattern_start(&parts[0]),
-            })),
+            Ok(mut parts) => {
+                let start = pattern_start(&parts[0]);
+                let mut end;
+                let expr = parts.remove(1).as_expr();
+                let mut patterns = Vec::new();
+                let mut guards = Vec::new();
+                let mut arms = Vec::new();
+                // the first arm that matches everything, to warn about the arms after it
+                let mut catch_all: Option<(usize, usize)> = None;
+                loop {
+                    if let Some(brace) = self
+                        .lexer
+                        .next_if(|token| token.kind == TokenType::RightBrace)
+                    {
+                        end = brace.start + brace.text.len();
+                        break;
+                    }
+                    let (pattern, pattern_start, pattern_end) =
+                        if let Some(pattern) = self.pattern() {
+                            pattern
+                        } else {
+                            return None;
+                        };
+                    if let Some(span) = catch_all {
+                        self.errors.push(
+                            "arm matches everything, so later arms never run",
+                            Severity::Warning,
+                            span.0,
+                            span.1,
+                            false,
+                        );
+                        catch_all = None;
+                    }
+                    end = pattern_end;
+                    self.scopes.push(HashMap::new());
+                    if let Some(name) = pattern.binding() {
+                        self.declare(name, false);
+                    }
+                    let arm = self.match_arm(end);
+                    self.scopes.pop();
+                    let (guard, arm) = if let Some(arm) = arm {
+                        arm
+                    } else {
+                        return None;
+                    };
+                    end = arm.start() + arm.len();
+                    arms.push(arm);
+                    if guard.is_none()
+                        && pattern.is_irrefutable()
+                        && patterns.len() == arms.len() - 1
+                    {
+                        catch_all =
+                            catch_all.or(Some((pattern_start, pattern_end - pattern_start)));
+                    }
+                    patterns.push(pattern);
+                    guards.push(guard);
+                    if let Some(comma) = self.lexer.next_if(|token| token.kind == TokenType::Comma)
+                    {
+                        end = comma.start + comma.text.len();
+                    }
+                    if let None = self.lexer.peek() {
+                        self.errors.push(
+                            "expected } after match arms",
+                            Severity::Error,
+                            end,
+                            0,
+                            true,
+                        );
+                        return None;
+                    }
+                }
+                Some(Box::new(MatchStmt {
+                    start,
+                    len: end - start,
+                    expr,
+                    patterns,
+                    guards,
+                    arms,
+                }))
+            }
         }
     }
 
-    fn while_statement(&mut self) -> Option<Box<dyn Stmt>> {
-        match self.try_match(vec![
-            (
-                Box::new(|parser| {
-                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::While))
-                }),
-                (true, "expected 'while'"),
-            ),
-            (
-                Box::new(|parser| pattern_expr(parser.expression())),
-                (false, "expected condition after 'while'"),
+    fn match_arm(&mut self, mut end: usize) -> Option<(Option<Box<dyn Expr>>, Box<dyn Stmt>)> {
+        // parses an optional guard, the => and the arm's block after a pattern ending at end
+        let guard = if let Some(token) = self.lexer.next_if(|token| token.kind == TokenType::If) {
+            if let Some(guard) = self.expression() {
+                end = guard.start() + guard.len();
+                Some(guard)
+            } else {
+                self.errors.push(
+                    "expected guard after 'if'",
+                    Severity::Error,
+                    token.start + token.text.len(),
+                    0,
+                    true,
+                );
+                return None;
+            }
+        } else {
+            None
+        };
+        if let None = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::FatArrow)
+        {
+            self.errors
+                .push("expected => after pattern", Severity::Error, end, 0, true);
+            return None;
+        }
+        if let Some(arm) = self.block_statement() {
+            Some((guard, arm))
+        } else {
+            self.errors
+                .push("expected block after =>", Severity::Error, end, 0, true);
+            None
+        }
+    }
+
+    fn pattern(&mut self) -> Option<(Pattern, usize, usize)> {
+        // parses alternatives separated by |, returning the pattern and its span
+        let mut alternatives = Vec::new();
+        let mut start = None;
+        let mut end;
+        loop {
+            let (pattern, pattern_start, pattern_end) = if let Some(pattern) = self.simple_pattern()
+            {
+                pattern
+            } else {
+                return None;
+            };
+            start = start.or(Some(pattern_start));
+            end = pattern_end;
+            alternatives.push((pattern, pattern_start, pattern_end));
+            if let None = self.lexer.next_if(|token| token.kind == TokenType::Pipe) {
+                break;
+            }
+        }
+        let start = start.unwrap();
+        if alternatives.len() == 1 {
+            let (pattern, _, _) = alternatives.remove(0);
+            return Some((pattern, start, end));
+        }
+        for (pattern, pattern_start, pattern_end) in &alternatives {
+            if let Some(_) = pattern.binding() {
+                self.errors.push(
+                    "can't bind a name in an alternation",
+                    Severity::Error,
+                    *pattern_start,
+                    pattern_end - pattern_start,
+                    false,
+                );
+            }
+        }
+        Some((
+            Pattern::Alternation(
+                alternatives
+                    .into_iter()
+                    .map(|(pattern, _, _)| pattern)
+                    .collect(),
+            ),
+            start,
+            end,
+        ))
+    }
+
+    fn simple_pattern(&mut self) -> Option<(Pattern, usize, usize)> {
+        let minus = self.lexer.next_if(|token| token.kind == TokenType::Minus);
+        if let Some(token) = self.lexer.next_if(|token| match token.kind {
+            TokenType::Literal | TokenType::Identifier => true,
+            _ => false,
+        }) {
+            let end = token.start + token.text.len();
+            if let Some(minus) = minus {
+                // negative numbers are the only patterns with an operator
+                return match token.value {
+                    LoxValue::Int(value) if token.kind == TokenType::Literal => {
+                        Some((Pattern::Literal(LoxValue::Int(-value)), minus.start, end))
+                    }
+                    LoxValue::Num(value) => {
+                        Some((Pattern::Literal(LoxValue::Num(-value)), minus.start, end))
+                    }
+                    _ => {
+                        self.errors.push(
+                            "expected number after - in pattern",
+                            Severity::Error,
+                            token.start,
+                            token.text.len(),
+                            true,
+                        );
+                        None
+                    }
+                };
+            }
+            match token.kind {
+                TokenType::Identifier if token.text == "_" => {
+                    Some((Pattern::Wildcard, token.start, end))
+                }
+                TokenType::Identifier => Some((Pattern::Binding(token.text), token.start, end)),
+                _ => Some((Pattern::Literal(token.value), token.start, end)),
+            }
+        } else {
+            let end = match (&minus, self.lexer.peek()) {
+                (Some(minus), _) => minus.start + minus.text.len(),
+                (None, Some(token)) => token.start,
+                (None, None) => self.source.len(),
+            };
+            self.errors
+                .push("expected pattern", Severity::Error, end, 0, true);
+            None
+        }
+    }
+
+    fn return_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        if let Some(token) = self.lexer.next_if(|token| token.kind == TokenType::Return) {
+            let expr = if let Some(_) = self
+                .lexer
+                .peek_if(|token| token.kind == TokenType::Semicolon)
+            {
+                None
+            } else if let Some(expr) = self.expression() {
+                Some(expr)
+            } else {
+                return None;
+            };
+            if let Some(semicolon) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::Semicolon)
+            {
+                if self.fn_kind == FnKind::None {
+                    self.errors.push(
+                        "can't return from top-level code",
+                        Severity::Error,
+                        token.start,
+                        token.text.len(),
+                        false,
+                    );
+                } else if let (FnKind::Initializer, Some(expr)) = (self.fn_kind, &expr) {
+                    self.errors.push(
+                        "can't return a value from an initializer",
+                        Severity::Error,
+                        expr.start(),
+                        expr.len(),
+                        false,
+                    );
+                }
+                Some(Box::new(ReturnStmt {
+                    start: token.start,
+                    len: semicolon.start + semicolon.text.len() - token.start,
+                    expr,
+                }))
+            } else {
+                let end = if let Some(expr) = &expr {
+                    expr.start() + expr.len()
+                } else {
+                    token.start + token.text.len()
+                };
+                self.errors.push(
+                    "expected ; after return statement",
+                    Severity::Error,
+                    end,
+                    0,
+                    true,
+                );
+                None
+            }
+        } else {
+            None
+        }
+    }
+
+    fn throw_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        match self.try_match(vec![
+            (
+                Box::new(|parser| {
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Throw))
+                }),
+                (true, "expected 'throw'"),
+            ),
+            (
+                Box::new(|parser| pattern_expr(parser.expression())),
+                (false, "expected value after 'throw'"),
+            ),
+            (
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Semicolon),
+                    )
+                }),
+                (false, "expected ; after throw statement"),
+            ),
+        ]) {
+            Err(_) => None,
+            Ok(mut parts) => Some(Box::new(ThrowStmt {
+                start: pattern_start(&parts[0]),
+                len: pattern_end(&parts[2]) - pattern_start(&parts[0]),
+                expr: parts.remove(1).as_expr(),
+            })),
+        }
+    }
+
+    fn try_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        match self.try_match(vec![
+            (
+                Box::new(|parser| {
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Try))
+                }),
+                (true, "expected 'try'"),
             ),
             (
                 Box::new(|parser| pattern_stmt(parser.block_statement())),
-                (false, "expected block after while statement"),
+                (false, "expected block after 'try'"),
             ),
         ]) {
+            Err(_) => None,
+            Ok(mut parts) => {
+                let start = pattern_start(&parts[0]);
+                let mut end = pattern_end(&parts[1]);
+                let stmt = parts.remove(1).as_stmt();
+                let mut name = None;
+                let mut catch_stmt = None;
+                if let Some(catch) = self.lexer.next_if(|token| token.kind == TokenType::Catch) {
+                    end = catch.start + catch.text.len();
+                    if let Some(token) = self
+                        .lexer
+                        .next_if(|token| token.kind == TokenType::Identifier)
+                    {
+                        end = token.start + token.text.len();
+                        name = Some(token.text);
+                    }
+                    self.scopes.push(HashMap::new());
+                    if let Some(name) = &name {
+                        self.declare(name, false);
+                    }
+                    let block = self.block_statement();
+                    self.scopes.pop();
+                    if let Some(block) = block {
+                        end = block.start() + block.len();
+                        catch_stmt = Some(block);
+                    } else {
+                        self.errors.push(
+                            "expected block after 'catch'",
+                            Severity::Error,
+                            end,
+                            0,
+                            true,
+                        );
+                        return None;
+                    }
+                }
+                let mut finally_stmt = None;
+                if let Some(finally) = self.lexer.next_if(|token| token.kind == TokenType::Finally)
+                {
+                    if let Some(block) = self.block_statement() {
+                        end = block.start() + block.len();
+                        finally_stmt = Some(block);
+                    } else {
+                        self.errors.push(
+                            "expected block after 'finally'",
+                            Severity::Error,
+                            finally.start + finally.text.len(),
+                            0,
+                            true,
+                        );
+                        return None;
+                    }
+                }
+                if catch_stmt.is_none() && finally_stmt.is_none() {
+                    self.errors.push(
+                        "expected 'catch' or 'finally' after try block",
+                        Severity::Error,
+                        end,
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+                Some(Box::new(TryStmt {
+                    start,
+                    len: end - start,
+                    stmt,
+                    name,
+                    catch_stmt,
+                    finally_stmt,
+                }))
+            }
+        }
+    }
+    fn while_statement(&mut self, label: Option<String>) -> Option<Box<dyn Stmt>> {
+        self.loop_labels.push(label.clone());
+        let parts = self.try_match(vec![
+            (
+                Box::new(|parser| {
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::While))
+                }),
+                (true, "expected 'while'"),
+            ),
+            (
+                Box::new(|parser| pattern_expr(parser.expression())),
+                (false, "expected condition after 'while'"),
+            ),
+            (
+                Box::new(|parser| pattern_stmt(parser.block_statement())),
+                (false, "expected block after while statement"),
+            ),
+        ]);
+        self.loop_labels.pop();
+        match parts {
             Err(_) => None,
             Ok(mut parts) => Some(Box::new(WhileStmt {
                 start: pattern_start(&parts[0]),
                 len: pattern_end(&parts[2]) - pattern_start(&parts[0]),
+                label,
                 expr: parts.remove(1).as_expr(),
                 stmt: parts.remove(1).as_stmt(),
+                incr: None,
             })),
         }
     }
 
+    fn labeled_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        match self.try_match(vec![
+            (
+                Box::new(|parser| {
+                    pattern_token(
+                        parser
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Identifier),
+                    )
+                }),
+                (true, "expected label"),
+            ),
+            (
+                Box::new(|parser| {
+                    pattern_token(parser.lexer.next_if(|token| token.kind == TokenType::Colon))
+                }),
+                (false, "expected : after label"),
+            ),
+        ]) {
+            Err(_) => None,
+            Ok(mut parts) => {
+                let label = Some(parts.remove(0).as_token().text);
+                if let Some(token) = self.lexer.peek() {
+                    match token.kind {
+                        TokenType::For => return self.for_statement(label),
+                        TokenType::While => return self.while_statement(label),
+                        _ => {}
+                    }
+                }
+                self.errors.push(
+                    "expected loop after label",
+                    Severity::Error,
+                    pattern_end(&parts[0]),
+                    0,
+                    true,
+                );
+                None
+            }
+        }
+    }
+
+    fn jump_statement(&mut self) -> Option<Box<dyn Stmt>> {
+        // parses a break or continue statement
+        if let Some(token) = self.lexer.next_if(|token| match token.kind {
+            TokenType::Break | TokenType::Continue => true,
+            _ => false,
+        }) {
+            let label = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::Identifier);
+            let end = if let Some(label) = &label {
+                label.start + label.text.len()
+            } else {
+                token.start + token.text.len()
+            };
+            if let Some(semicolon) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::Semicolon)
+            {
+                if self.loop_labels.is_empty() {
+                    self.errors.push(
+                        if token.kind == TokenType::Break {
+                            "can't use 'break' outside of a loop"
+                        } else {
+                            "can't use 'continue' outside of a loop"
+                        },
+                        Severity::Error,
+                        token.start,
+                        token.text.len(),
+                        false,
+                    );
+                } else if let Some(label) = &label {
+                    if !self.loop_labels.contains(&Some(label.text.to_string())) {
+                        self.errors.push(
+                            "no enclosing loop with this label",
+                            Severity::Error,
+                            label.start,
+                            label.text.len(),
+                            false,
+                        );
+                    }
+                }
+                let start = token.start;
+                let len = semicolon.start + semicolon.text.len() - start;
+                let label = label.map(|label| label.text);
+                if token.kind == TokenType::Break {
+                    Some(Box::new(BreakStmt { start, len, label }))
+                } else {
+                    Some(Box::new(ContinueStmt { start, len, label }))
+                }
+            } else {
+                self.errors.push(
+                    if token.kind == TokenType::Break {
+                        "expected ; after break statement"
+                    } else {
+                        "expected ; after continue statement"
+                    },
+                    Severity::Error,
+                    end,
+                    0,
+                    true,
+                );
+                None
+            }
+        } else {
+            None
+        }
+    }
+
     fn expression(&mut self) -> Option<Box<dyn Expr>> {
         self.assignment()
     }
 
     fn assignment(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(expr) = self.conditional() {
+            if let Some(equal) = self.lexer.next_if(|token| match token.kind {
+                TokenType::Equal
+                | TokenType::PlusEqual
+                | TokenType::MinusEqual
+                | TokenType::StarEqual
+                | TokenType::SlashEqual
+                | TokenType::PercentEqual => true,
+                _ => false,
+            }) {
+                let oper = match equal.kind {
+                    TokenType::PlusEqual => Some(TokenType::Plus),
+                    TokenType::MinusEqual => Some(TokenType::Minus),
+                    TokenType::StarEqual => Some(TokenType::Star),
+                    TokenType::SlashEqual => Some(TokenType::Slash),
+                    TokenType::PercentEqual => Some(TokenType::Percent),
+                    _ => None,
+                };
+                if let Some(value) = self.assignment() {
+                    let (start, len) = (expr.start(), value.start() - expr.start() + value.len());
+                    self.assign_target(expr, oper, false, value, start, len)
+                } else {
+                    self.errors.push(
+                        "expected value after assignment",
+                        Severity::Error,
+                        equal.start + equal.text.len(),
+                        0,
+                        true,
+                    );
+                    None
+                }
+            } else {
+                Some(expr)
+            }
+        } else {
+            None
+        }
+    }
+
+    fn assign_target(
+        &mut self,
+        target: Box<dyn Expr>,
+        oper: Option<TokenType>,
+        postfix: bool,
+        expr: Box<dyn Expr>,
+        start: usize,
+        len: usize,
+    ) -> Option<Box<dyn Expr>> {
+        // turns a parsed target expression into the matching assignment, so compound
+        // assignments evaluate their target only once; 'this' parses as a variable but
+        // can't be assigned to
+        if let Some(var) = target.as_varget().filter(|var| var.name != "this") {
+            if self.is_constant(&var.name) {
+                self.constant_error(target.start(), target.len());
+                return None;
+            }
+            Some(Box::new(VarSet {
+                start,
+                len,
+                name: var.name.to_string(),
+                oper,
+                postfix,
+                expr,
+            }))
+        } else if target.as_get().is_some() {
+            let get = target.to_get().unwrap();
+            Some(Box::new(Set {
+                start,
+                len,
+                object: get.object,
+                name: get.name,
+                oper,
+                postfix,
+                expr,
+            }))
+        } else if target.as_indexget().is_some() {
+            let get = target.to_indexget().unwrap();
+            Some(Box::new(IndexSet {
+                start,
+                len,
+                object: get.object,
+                index: get.index,
+                oper,
+                postfix,
+                expr,
+            }))
+        } else if target.as_list().is_some() && oper.is_none() {
+            let list = target.to_list().unwrap();
+            if let Some(target) = self.list_target(list) {
+                Some(Box::new(DestructureSet {
+                    start,
+                    len,
+                    target,
+                    expr,
+                }))
+            } else {
+                None
+            }
+        } else {
+            self.errors.push(
+                "invalid assignment target",
+                Severity::Error,
+                target.start(),
+                target.len(),
+                true,
+            );
+            None
+        }
+    }
+
+    fn list_target(&mut self, list: List) -> Option<Destructure> {
+        // a list literal on the left of = destructures into the names it lists
+        let mut items = Vec::new();
+        for item in list.items {
+            if let Some(var) = item.as_varget().filter(|var| var.name != "this") {
+                if self.is_constant(&var.name) {
+                    self.constant_error(item.start(), item.len());
+                    return None;
+                }
+                items.push(Destructure::Name {
+                    name: var.name.to_string(),
+                    span: (item.start(), item.len()),
+                });
+            } else if item.as_list().is_some() {
+                if let Some(nested) = self.list_target(item.to_list().unwrap()) {
+                    items.push(nested);
+                } else {
+                    return None;
+                }
+            } else {
+                self.errors.push(
+                    "invalid assignment target",
+                    Severity::Error,
+                    item.start(),
+                    item.len(),
+                    true,
+                );
+                return None;
+            }
+        }
+        Some(Destructure::List {
+            items,
+            rest: None,
+            span: (list.start, list.len),
+        })
+    }
+
+    fn declare(&mut self, name: &str, constant: bool) -> bool {
+        // returns false, leaving the constant in place, if the name is a constant of this scope
+        if let Some(scope) = self.scopes.last_mut() {
+            if let Some(true) = scope.get(name) {
+                return false;
+            }
+            scope.insert(name.to_string(), constant);
+        }
+        true
+    }
+
+    fn redeclare_error(&mut self, start: usize, len: usize) {
+        self.errors.push(
+            "can't redeclare a constant",
+            Severity::Error,
+            start,
+            len,
+            false,
+        );
+    }
+
+    fn is_constant(&self, name: &str) -> bool {
+        // names declared outside this file aren't known here, so the VM checks them when assigned
+        for scope in self.scopes.iter().rev() {
+            if let Some(constant) = scope.get(name) {
+                return *constant;
+            }
+        }
+        false
+    }
+
+    fn constant_error(&mut self, start: usize, len: usize) {
+        self.errors.push(
+            "can't assign to a constant",
+            Severity::Error,
+            start,
+            len,
+            true,
+        );
+    }
+
+    fn conditional(&mut self) -> Option<Box<dyn Expr>> {
         if let Some(expr) = self.logic_or() {
-            if let Some(equal) = self.lexer.next_if(|token| token.kind == TokenType::Equal) {
-                if let Some(var) = expr.as_varget() {
-                    if let Some(value) = self.assignment() {
-                        Some(Box::new(VarSet {
-                            start: expr.start(),
-                            len: value.start() - expr.start() + value.len(),
-                            name: var.name.to_string(),
-                            expr: value,
-                        }))
-                    } else {
+            if let Some(question) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::Question)
+            {
+                let expr_true = match self.expression() {
+                    Some(expr) => expr,
+                    None => {
                         self.errors.push(
-                            "expected value after assignment",
+                            "expected expression after ?",
                             Severity::Error,
-                            equal.start + equal.text.len(),
+                            question.start + question.text.len(),
                             0,
                             true,
                         );
-                        None
+                        return None;
+                    }
+                };
+                let colon = match self.lexer.next_if(|token| token.kind == TokenType::Colon) {
+                    Some(colon) => colon,
+                    None => {
+                        self.errors.push(
+                            "expected : after conditional branch",
+                            Severity::Error,
+                            expr_true.start() + expr_true.len(),
+                            0,
+                            true,
+                        );
+                        return None;
                     }
+                };
+                // the false branch is parsed at this level so conditionals nest to the right
+                if let Some(expr_false) = self.conditional() {
+                    Some(Box::new(Conditional {
+                        start: expr.start(),
+                        len: expr_false.start() - expr.start() + expr_false.len(),
+                        expr,
+                        expr_true,
+                        expr_false,
+                    }))
                 } else {
                     self.errors.push(
-                        "invalid assignment target",
+                        "expected expression after :",
                         Severity::Error,
-                        expr.start(),
-                        expr.len(),
+                        colon.start + colon.text.len(),
+                        0,
                         true,
                     );
                     None
                 }
-            } else {
-                Some(expr)
+            } else {
+                Some(expr)
+            }
+        } else {
+            None
+        }
+    }
+
+    fn logic_or(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(mut left) = self.logic_and() {
+            while let Some(oper) = self.lexer.next_if(|token| token.kind == TokenType::Or) {
+                if let Some(right) = self.logic_and() {
+                    left = Box::new(Logical {
+                        start: left.start(),
+                        len: right.start() - left.start() + right.len(),
+                        left,
+                        oper: oper.kind,
+                        right,
+                    })
+                } else {
+                    self.errors.push(
+                        "expected expression after operator",
+                        Severity::Error,
+                        oper.start + oper.text.len(),
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+            }
+            Some(left)
+        } else {
+            if let Some(token) = self.lexer.peek() {
+                self.errors.push(
+                    "expected expression",
+                    Severity::Error,
+                    token.start,
+                    token.text.len(),
+                    true,
+                );
+            }
+            None
+        }
+    }
+
+    fn logic_and(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(mut left) = self.equality() {
+            while let Some(oper) = self.lexer.next_if(|token| token.kind == TokenType::And) {
+                if let Some(right) = self.equality() {
+                    left = Box::new(Logical {
+                        start: left.start(),
+                        len: right.start() - left.start() + right.len(),
+                        left,
+                        oper: oper.kind,
+                        right,
+                    })
+                } else {
+                    self.errors.push(
+                        "expected expression after operator",
+                        Severity::Error,
+                        oper.start + oper.text.len(),
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+            }
+            Some(left)
+        } else {
+            if let Some(token) = self.lexer.peek() {
+                self.errors.push(
+                    "expected expression",
+                    Severity::Error,
+                    token.start,
+                    token.text.len(),
+                    true,
+                );
+            }
+            None
+        }
+    }
+
+    fn equality(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(mut left) = self.bitwise_or() {
+            while let Some(oper) = self.lexer.next_if(|token| match token.kind {
+                TokenType::EqualEqual | TokenType::BangEqual => true,
+                _ => false,
+            }) {
+                if let Some(right) = self.bitwise_or() {
+                    left = Box::new(Binary {
+                        start: left.start(),
+                        len: right.start() - left.start() + right.len(),
+                        left,
+                        oper: oper.kind,
+                        right,
+                    })
+                } else {
+                    self.errors.push(
+                        "expected expression after operator",
+                        Severity::Error,
+                        oper.start + oper.text.len(),
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+            }
+            Some(left)
+        } else {
+            if let Some(token) = self.lexer.peek() {
+                self.errors.push(
+                    "expected expression",
+                    Severity::Error,
+                    token.start,
+                    token.text.len(),
+                    true,
+                );
+            }
+            None
+        }
+    }
+
+    fn bitwise_or(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(mut left) = self.bitwise_xor() {
+            while let Some(oper) = self.lexer.next_if(|token| match token.kind {
+                TokenType::Pipe => true,
+                _ => false,
+            }) {
+                if let Some(right) = self.bitwise_xor() {
+                    left = Box::new(Binary {
+                        start: left.start(),
+                        len: right.start() - left.start() + right.len(),
+                        left,
+                        oper: oper.kind,
+                        right,
+                    })
+                } else {
+                    self.errors.push(
+                        "expected expression after operator",
+                        Severity::Error,
+                        oper.start + oper.text.len(),
+                        0,
+                        true,
+                    );
+                    return None;
+                }
             }
+            Some(left)
         } else {
             None
         }
     }
 
-    fn logic_or(&mut self) -> Option<Box<dyn Expr>> {
-        if let Some(mut left) = self.logic_and() {
-            while let Some(oper) = self.lexer.next_if(|token| token.kind == TokenType::Or) {
-                if let Some(right) = self.logic_and() {
-                    left = Box::new(Logical {
+    fn bitwise_xor(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(mut left) = self.bitwise_and() {
+            while let Some(oper) = self.lexer.next_if(|token| match token.kind {
+                TokenType::Caret => true,
+                _ => false,
+            }) {
+                if let Some(right) = self.bitwise_and() {
+                    left = Box::new(Binary {
                         start: left.start(),
                         len: right.start() - left.start() + right.len(),
                         left,
@@ -604,24 +2294,18 @@ impl Parser<'_> {
             }
             Some(left)
         } else {
-            if let Some(token) = self.lexer.peek() {
-                self.errors.push(
-                    "expected expression",
-                    Severity::Error,
-                    token.start,
-                    token.text.len(),
-                    true,
-                );
-            }
             None
         }
     }
 
-    fn logic_and(&mut self) -> Option<Box<dyn Expr>> {
-        if let Some(mut left) = self.equality() {
-            while let Some(oper) = self.lexer.next_if(|token| token.kind == TokenType::And) {
-                if let Some(right) = self.equality() {
-                    left = Box::new(Logical {
+    fn bitwise_and(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(mut left) = self.shift() {
+            while let Some(oper) = self.lexer.next_if(|token| match token.kind {
+                TokenType::Ampersand => true,
+                _ => false,
+            }) {
+                if let Some(right) = self.shift() {
+                    left = Box::new(Binary {
                         start: left.start(),
                         len: right.start() - left.start() + right.len(),
                         left,
@@ -641,23 +2325,16 @@ impl Parser<'_> {
             }
             Some(left)
         } else {
-            if let Some(token) = self.lexer.peek() {
-                self.errors.push(
-                    "expected expression",
-                    Severity::Error,
-                    token.start,
-                    token.text.len(),
-                    true,
-                );
-            }
             None
         }
     }
 
-    fn equality(&mut self) -> Option<Box<dyn Expr>> {
+    fn shift(&mut self) -> Option<Box<dyn Expr>> {
         if let Some(mut left) = self.comparison() {
             while let Some(oper) = self.lexer.next_if(|token| match token.kind {
-                TokenType::EqualEqual | TokenType::BangEqual => true,
+                TokenType::LessLess
+                | TokenType::GreaterGreater
+                | TokenType::GreaterGreaterGreater => true,
                 _ => false,
             }) {
                 if let Some(right) = self.comparison() {
@@ -681,15 +2358,6 @@ impl Parser<'_> {
             }
             Some(left)
         } else {
-            if let Some(token) = self.lexer.peek() {
-                self.errors.push(
-                    "expected expression",
-                    Severity::Error,
-                    token.start,
-                    token.text.len(),
-                    true,
-                );
-            }
             None
         }
     }
@@ -762,7 +2430,9 @@ impl Parser<'_> {
     fn factor(&mut self) -> Option<Box<dyn Expr>> {
         if let Some(mut left) = self.unary() {
             while let Some(oper) = self.lexer.next_if(|token| match token.kind {
-                TokenType::Star | TokenType::Slash | TokenType::Percent => true,
+                TokenType::Star | TokenType::Slash | TokenType::Percent | TokenType::TildeSlash => {
+                    true
+                }
                 _ => false,
             }) {
                 if let Some(right) = self.unary() {
@@ -792,7 +2462,7 @@ impl Parser<'_> {
 
     fn unary(&mut self) -> Option<Box<dyn Expr>> {
         if let Some(token) = self.lexer.next_if(|token| match token.kind {
-            TokenType::Bang | TokenType::Minus => true,
+            TokenType::Bang | TokenType::Minus | TokenType::Tilde => true,
             _ => false,
         }) {
             if let Some(expr) = self.unary() {
@@ -812,59 +2482,224 @@ impl Parser<'_> {
                 );
                 None
             }
+        } else if let Some(token) = self.lexer.next_if(|token| match token.kind {
+            TokenType::PlusPlus | TokenType::MinusMinus => true,
+            _ => false,
+        }) {
+            if let Some(target) = self.unary() {
+                let (start, len) = (token.start, target.start() - token.start + target.len());
+                let (oper, one) = Self::increment(&token);
+                self.assign_target(target, Some(oper), false, one, start, len)
+            } else {
+                self.errors.push(
+                    "expected expression after operator",
+                    Severity::Error,
+                    token.start + token.text.len(),
+                    0,
+                    true,
+                );
+                None
+            }
         } else {
-            self.call()
+            self.power()
         }
     }
 
-    fn call(&mut self) -> Option<Box<dyn Expr>> {
-        if let Some(primary) = self.primary() {
+    fn power(&mut self) -> Option<Box<dyn Expr>> {
+        // the exponent is parsed as a unary, so 2 ** -1 works and 2 ** 3 ** 2 groups to the right
+        if let Some(left) = self.postfix() {
+            if let Some(oper) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::StarStar)
+            {
+                if let Some(right) = self.unary() {
+                    Some(Box::new(Binary {
+                        start: left.start(),
+                        len: right.start() - left.start() + right.len(),
+                        left,
+                        oper: oper.kind,
+                        right,
+                    }))
+                } else {
+                    self.errors.push(
+                        "expected expression after operator",
+                        Severity::Error,
+                        oper.start + oper.text.len(),
+                        0,
+                        true,
+                    );
+                    None
+                }
+            } else {
+                Some(left)
+            }
+        } else {
+            None
+        }
+    }
+
+    fn postfix(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(target) = self.call() {
             if let Some(token) = self.lexer.next_if(|token| match token.kind {
-                TokenType::LeftParen => true,
+                TokenType::PlusPlus | TokenType::MinusMinus => true,
                 _ => false,
             }) {
-                let mut args = Vec::new();
-                loop {
-                    if let Some(token) = self.lexer.next_if(|token| match token.kind {
-                        TokenType::RightParen => true,
-                        _ => false,
-                    }) {
-                        return Some(Box::new(Call {
-                            start: primary.start(),
-                            len: token.start + token.text.len() - primary.start(),
-                            callee: primary,
-                            args,
-                        }));
-                    } else {
-                        self.lexer.next_if(|token| match token.kind {
-                            TokenType::Comma => true,
-                            _ => false,
-                        });
-                        if let Some(arg) = self.expression() {
-                            if let Some(comma) = self.lexer.peek_if(|token| match token.kind {
-                                TokenType::Comma | TokenType::RightParen => true,
-                                _ => false,
-                            }) {
-                                args.push(arg);
-                            } else {
-                                return None;
-                            }
+                let (start, len) = (
+                    target.start(),
+                    token.start + token.text.len() - target.start(),
+                );
+                let (oper, one) = Self::increment(&token);
+                self.assign_target(target, Some(oper), true, one, start, len)
+            } else {
+                Some(target)
+            }
+        } else {
+            None
+        }
+    }
+
+    fn increment(token: &Token) -> (TokenType, Box<dyn Expr>) {
+        // ++ and -- add or subtract a literal 1 spanning the operator
+        let oper = if token.kind == TokenType::PlusPlus {
+            TokenType::Plus
+        } else {
+            TokenType::Minus
+        };
+        (
+            oper,
+            Box::new(Literal {
+                start: token.start,
+                len: token.text.len(),
+                value: LoxValue::Int(1),
+            }),
+        )
+    }
+
+    fn call(&mut self) -> Option<Box<dyn Expr>> {
+        if let Some(mut expr) = self.primary() {
+            while let Some(token) = self.lexer.next_if(|token| match token.kind {
+                TokenType::LeftParen | TokenType::LeftBracket | TokenType::Dot => true,
+                _ => false,
+            }) {
+                if token.kind == TokenType::LeftBracket {
+                    if let Some(index) = self.expression() {
+                        if let Some(bracket) = self
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::RightBracket)
+                        {
+                            expr = Box::new(IndexGet {
+                                start: expr.start(),
+                                len: bracket.start + bracket.text.len() - expr.start(),
+                                object: expr,
+                                index,
+                            });
                         } else {
+                            self.errors.push(
+                                "expected ] after index",
+                                Severity::Error,
+                                index.start() + index.len(),
+                                0,
+                                true,
+                            );
                             return None;
                         }
+                    } else {
+                        return None;
+                    }
+                } else if token.kind == TokenType::Dot {
+                    if let Some(name) = self
+                        .lexer
+                        .next_if(|token| token.kind == TokenType::Identifier)
+                    {
+                        expr = Box::new(Get {
+                            start: expr.start(),
+                            len: name.start + name.text.len() - expr.start(),
+                            object: expr,
+                            name: name.text,
+                        });
+                    } else {
+                        self.errors.push(
+                            "expected property name after .",
+                            Severity::Error,
+                            token.start + token.text.len(),
+                            0,
+                            true,
+                        );
+                        return None;
                     }
+                } else if let Some(call) = self.finish_call(expr, token.start + token.text.len()) {
+                    expr = call;
+                } else {
+                    return None;
                 }
-            } else {
-                Some(primary)
             }
+            Some(expr)
         } else {
             None
         }
     }
 
+    fn finish_call(&mut self, callee: Box<dyn Expr>, start: usize) -> Option<Box<dyn Expr>> {
+        // parses an argument list whose left paren ends at start
+        let mut args: Vec<Box<dyn Expr>> = Vec::new();
+        let mut end = start;
+        if let Some(token) = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::RightParen)
+        {
+            return Some(Box::new(Call {
+                start: callee.start(),
+                len: token.start + token.text.len() - callee.start(),
+                callee,
+                args,
+            }));
+        }
+        loop {
+            if let Some(arg) = self.expression() {
+                end = arg.start() + arg.len();
+                args.push(arg);
+            } else {
+                self.errors
+                    .push("expected argument", Severity::Error, end, 0, true);
+                return None;
+            }
+            if let Some(token) = self.lexer.next_if(|token| match token.kind {
+                TokenType::Comma | TokenType::RightParen => true,
+                _ => false,
+            }) {
+                if token.kind == TokenType::RightParen {
+                    return Some(Box::new(Call {
+                        start: callee.start(),
+                        len: token.start + token.text.len() - callee.start(),
+                        callee,
+                        args,
+                    }));
+                }
+                end = token.start + token.text.len();
+            } else {
+                self.errors.push(
+                    "expected , or ) after argument",
+                    Severity::Error,
+                    end,
+                    0,
+                    true,
+                );
+                return None;
+            }
+        }
+    }
+
     fn primary(&mut self) -> Option<Box<dyn Expr>> {
         if let Some(token) = self.lexer.next_if(|token| match token.kind {
-            TokenType::Literal | TokenType::Identifier | TokenType::LeftParen => true,
+            TokenType::Literal
+            | TokenType::Identifier
+            | TokenType::LeftParen
+            | TokenType::LeftBracket
+            | TokenType::LeftBrace
+            | TokenType::InterpolationStart
+            | TokenType::Fn
+            | TokenType::Super
+            | TokenType::This => true,
             _ => false,
         }) {
             match token.kind {
@@ -878,6 +2713,75 @@ impl Parser<'_> {
                     len: token.text.len(),
                     name: token.text,
                 })),
+                TokenType::Fn => self.lambda(token),
+                TokenType::LeftBracket => self.list(token),
+                TokenType::LeftBrace => self.map(token),
+                TokenType::InterpolationStart => self.interpolation(token),
+                TokenType::Super => {
+                    match self.class_kind {
+                        ClassKind::None => self.errors.push(
+                            "can't use 'super' outside of a class",
+                            Severity::Error,
+                            token.start,
+                            token.text.len(),
+                            false,
+                        ),
+                        ClassKind::Class => self.errors.push(
+                            "can't use 'super' in a class with no superclass",
+                            Severity::Error,
+                            token.start,
+                            token.text.len(),
+                            false,
+                        ),
+                        ClassKind::Subclass => {}
+                    }
+                    if let Some(dot) = self.lexer.next_if(|token| token.kind == TokenType::Dot) {
+                        if let Some(method) = self
+                            .lexer
+                            .next_if(|token| token.kind == TokenType::Identifier)
+                        {
+                            Some(Box::new(Super {
+                                start: token.start,
+                                len: method.start + method.text.len() - token.start,
+                                method: method.text,
+                            }))
+                        } else {
+                            self.errors.push(
+                                "expected superclass method name",
+                                Severity::Error,
+                                dot.start + dot.text.len(),
+                                0,
+                                true,
+                            );
+                            None
+                        }
+                    } else {
+                        self.errors.push(
+                            "expected . after 'super'",
+                            Severity::Error,
+                            token.start + token.text.len(),
+                            0,
+                            true,
+                        );
+                        None
+                    }
+                }
+                TokenType::This => {
+                    if self.class_kind == ClassKind::None {
+                        self.errors.push(
+                            "can't use 'this' outside of a class",
+                            Severity::Error,
+                            token.start,
+                            token.text.len(),
+                            false,
+                        );
+                    }
+                    Some(Box::new(VarGet {
+                        start: token.start,
+                        len: token.text.len(),
+                        name: token.text,
+                    }))
+                }
                 TokenType::LeftParen => {
                     if let Some(expr) = self.expression() {
                         if let Some(rtoken) = self.lexer.next() {
@@ -926,6 +2830,177 @@ impl Parser<'_> {
         }
     }
 
+    fn interpolation(&mut self, token: Token) -> Option<Box<dyn Expr>> {
+        // parses the expressions and string parts following the first part of a string
+        let mut parts: Vec<Box<dyn Expr>> = vec![Box::new(Literal {
+            start: token.start,
+            len: token.text.len(),
+            value: token.value,
+        })];
+        loop {
+            let end = if let Some(expr) = self.expression() {
+                let end = expr.start() + expr.len();
+                parts.push(expr);
+                end
+            } else {
+                return None;
+            };
+            match self.lexer.next_if(|token| match token.kind {
+                TokenType::InterpolationMid | TokenType::InterpolationEnd => true,
+                _ => false,
+            }) {
+                Some(part) => {
+                    let kind = part.kind;
+                    let len = part.text.len();
+                    parts.push(Box::new(Literal {
+                        start: part.start,
+                        len,
+                        value: part.value,
+                    }));
+                    if kind == TokenType::InterpolationEnd {
+                        return Some(Box::new(Interpolation {
+                            start: token.start,
+                            len: part.start + len - token.start,
+                            parts,
+                        }));
+                    }
+                }
+                None => {
+                    self.errors.push(
+                        "expected } after interpolated expression",
+                        Severity::Error,
+                        end,
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn lambda(&mut self, token: Token) -> Option<Box<dyn Expr>> {
+        if let Some(paren) = self
+            .lexer
+            .next_if(|token| token.kind == TokenType::LeftParen)
+        {
+            if let Some(params) = self.parameters(paren.start + paren.text.len()) {
+                if let Some((body, end)) = self.function_body(FnKind::Function, &params) {
+                    Some(Box::new(Lambda {
+                        start: token.start,
+                        len: end - token.start,
+                        params: Rc::new(params),
+                        body: Rc::new(body),
+                    }))
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        } else {
+            self.errors.push(
+                "expected ( after 'fn'",
+                Severity::Error,
+                token.start + token.text.len(),
+                0,
+                true,
+            );
+            None
+        }
+    }
+
+    fn list(&mut self, bracket: Token) -> Option<Box<dyn Expr>> {
+        // parses list items after the opening bracket, allowing a trailing comma
+        let mut items: Vec<Box<dyn Expr>> = Vec::new();
+        let mut end;
+        loop {
+            if let Some(token) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::RightBracket)
+            {
+                return Some(Box::new(List {
+                    start: bracket.start,
+                    len: token.start + token.text.len() - bracket.start,
+                    items,
+                }));
+            }
+            if let Some(item) = self.expression() {
+                end = item.start() + item.len();
+                items.push(item);
+            } else {
+                return None;
+            }
+            if let None = self.lexer.next_if(|token| token.kind == TokenType::Comma) {
+                if let None = self
+                    .lexer
+                    .peek_if(|token| token.kind == TokenType::RightBracket)
+                {
+                    self.errors.push(
+                        "expected , or ] after list item",
+                        Severity::Error,
+                        end,
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+
+    fn map(&mut self, brace: Token) -> Option<Box<dyn Expr>> {
+        // parses map entries after the opening brace, allowing a trailing comma
+        let mut keys: Vec<Box<dyn Expr>> = Vec::new();
+        let mut values: Vec<Box<dyn Expr>> = Vec::new();
+        let mut end;
+        loop {
+            if let Some(token) = self
+                .lexer
+                .next_if(|token| token.kind == TokenType::RightBrace)
+            {
+                return Some(Box::new(Map {
+                    start: brace.start,
+                    len: token.start + token.text.len() - brace.start,
+                    keys,
+                    values,
+                }));
+            }
+            if let Some(key) = self.expression() {
+                end = key.start() + key.len();
+                keys.push(key);
+            } else {
+                return None;
+            }
+            if let None = self.lexer.next_if(|token| token.kind == TokenType::Colon) {
+                self.errors
+                    .push("expected : after map key", Severity::Error, end, 0, true);
+                return None;
+            }
+            if let Some(value) = self.expression() {
+                end = value.start() + value.len();
+                values.push(value);
+            } else {
+                return None;
+            }
+            if let None = self.lexer.next_if(|token| token.kind == TokenType::Comma) {
+                if let None = self
+                    .lexer
+                    .peek_if(|token| token.kind == TokenType::RightBrace)
+                {
+                    self.errors.push(
+                        "expected , or } after map entry",
+                        Severity::Error,
+                        end,
+                        0,
+                        true,
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+
     fn true_stmt() -> Box<dyn Stmt> {
         // this statement should never be able to cause a runtime error
         // so it doesn't need accurate position information
@@ -967,6 +3042,13 @@ fn pattern_end(elem: &PatternElem) -> usize {
     pattern_start(elem) + pattern_len(elem)
 }
 
+fn pattern_doc(elem: &PatternElem) -> Option<String> {
+    match elem {
+        PatternElem::Token(token) => token.doc.clone(),
+        PatternElem::Expr(_) | PatternElem::Stmt(_) => None,
+    }
+}
+
 fn pattern_token(token: Option<Token>) -> Option<PatternElem> {
     if let Some(token) = token {
         Some(PatternElem::Token(token))
@@ -0,0 +1,192 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::{Backtrace, LoxInstance, LoxValue, MapKey};
+
+/// Pattern: the left side of a match arm.
+pub enum Pattern {
+    Literal(LoxValue),
+    Binding(String),
+    Wildcard,
+    Alternation(Vec<Pattern>),
+}
+
+impl Pattern {
+    pub fn matches(&self, value: &LoxValue) -> bool {
+        match self {
+            Pattern::Literal(literal) => literal == value,
+            Pattern::Binding(_) | Pattern::Wildcard => true,
+            Pattern::Alternation(patterns) => patterns.iter().any(|pattern| pattern.matches(value)),
+        }
+    }
+
+    /// binding: the name the matched value is bound to, if any.
+    pub fn binding(&self) -> Option<&String> {
+        match self {
+            Pattern::Binding(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// is_irrefutable: whether the pattern matches every value.
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Pattern::Literal(_) => false,
+            Pattern::Binding(_) | Pattern::Wildcard => true,
+            Pattern::Alternation(patterns) => patterns.iter().any(Pattern::is_irrefutable),
+        }
+    }
+}
+
+/// Destructure: the left side of a destructuring declaration or assignment.
+/// Every element keeps its span so a failed match can point at it.
+pub enum Destructure {
+    Name {
+        name: String,
+        span: (usize, usize),
+    },
+    List {
+        items: Vec<Destructure>,
+        rest: Option<(String, (usize, usize))>,
+        span: (usize, usize),
+    },
+    Object {
+        names: Vec<(String, (usize, usize))>,
+        span: (usize, usize),
+    },
+}
+
+impl Destructure {
+    pub fn span(&self) -> (usize, usize) {
+        match self {
+            Destructure::Name { span, .. }
+            | Destructure::List { span, .. }
+            | Destructure::Object { span, .. } => *span,
+        }
+    }
+
+    /// names: every name the pattern binds, in order.
+    pub fn names(&self) -> Vec<&String> {
+        match self {
+            Destructure::Name { name, .. } => vec![name],
+            Destructure::List { items, rest, .. } => items
+                .iter()
+                .flat_map(Destructure::names)
+                .chain(rest.iter().map(|(name, _)| name))
+                .collect(),
+            Destructure::Object { names, .. } => names.iter().map(|(name, _)| name).collect(),
+        }
+    }
+
+    /// bind: match a value against the pattern, collecting each name with its span and value.
+    pub fn bind(
+        &self,
+        value: LoxValue,
+        bindings: &mut Vec<(String, (usize, usize), LoxValue)>,
+    ) -> Result<(), Backtrace> {
+        match self {
+            Destructure::Name { name, span } => {
+                bindings.push((name.to_string(), *span, value));
+                Ok(())
+            }
+            Destructure::List { items, rest, span } => {
+                let list = match value {
+                    LoxValue::List(list) => list.borrow().clone(),
+                    _ => {
+                        return Err(Backtrace::starting_at(
+                            format!("expected a list to destructure"),
+                            *span,
+                        ))
+                    }
+                };
+                if list.len() < items.len() {
+                    let item = &items[list.len()];
+                    let name = match item {
+                        Destructure::Name { name, .. } => format!("'{}'", name),
+                        _ => format!("the nested pattern"),
+                    };
+                    return Err(Backtrace::starting_at(
+                        format!("no element at index {} for {}", list.len(), name),
+                        item.span(),
+                    ));
+                }
+                if rest.is_none() && list.len() > items.len() {
+                    return Err(Backtrace::starting_at(
+                        format!(
+                            "list has {} elements but the pattern takes {}",
+                            list.len(),
+                            items.len()
+                        ),
+                        *span,
+                    ));
+                }
+                for (item, value) in items.iter().zip(list.iter()) {
+                    if let Err(err) = item.bind(value.clone(), bindings) {
+                        return Err(err);
+                    }
+                }
+                if let Some((name, span)) = rest {
+                    let rest = list[items.len()..].to_vec();
+                    bindings.push((
+                        name.to_string(),
+                        *span,
+                        LoxValue::List(Rc::new(RefCell::new(rest))),
+                    ));
+                }
+                Ok(())
+            }
+            Destructure::Object { names, span } => {
+                for (name, name_span) in names {
+                    let field = match &value {
+                        LoxValue::Map(map) => {
+                            match map.borrow().get(&MapKey::Str(name.to_string())) {
+                                Some(field) => field,
+                                None => {
+                                    return Err(Backtrace::starting_at(
+                                        format!("map has no key '{}'", name),
+                                        *name_span,
+                                    ))
+                                }
+                            }
+                        }
+                        LoxValue::Instance(instance) => match LoxInstance::get(instance, name) {
+                            Some(field) => field,
+                            None => {
+                                return Err(Backtrace::starting_at(
+                                    format!("instance has no field '{}'", name),
+                                    *name_span,
+                                ))
+                            }
+                        },
+                        _ => {
+                            return Err(Backtrace::starting_at(
+                                format!("expected a map or instance to destructure"),
+                                *span,
+                            ))
+                        }
+                    };
+                    bindings.push((name.to_string(), *name_span, field));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Destructure {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            Destructure::Name { name, .. } => formatter.write_str(name),
+            Destructure::List { items, rest, .. } => {
+                let mut parts: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                if let Some((name, _)) = rest {
+                    parts.push(format!("...{}", name));
+                }
+                write!(formatter, "[{}]", parts.join(", "))
+            }
+            Destructure::Object { names, .. } => {
+                let names: Vec<&str> = names.iter().map(|(name, _)| name.as_str()).collect();
+                write!(formatter, "{{{}}}", names.join(", "))
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
 pub mod vm;
 
+use std::rc::Rc;
+
 use super::expr::Expr;
+use super::pattern::{Destructure, Pattern};
 
-// impl Visitor<Result<(), Backtrace>> for VM: run;
-use super::{Backtrace, VM};
+// impl Visitor<Result<(), Unwind>> for VM: run;
+use super::{Unwind, VM};
 
 // trait: Stmt;
 // attr start: usize;
@@ -11,11 +14,20 @@ use super::{Backtrace, VM};
 // cast: ExprStmt;
 
 // type BlockStmt: stmts: Vec<Box<dyn Stmt>>;
+// type BreakStmt: label: Option<String>;
+// type ClassStmt: name: String, superclass: Option<Box<dyn Expr>>, methods: Vec<FnStmt>, doc: Option<String>;
+// type ContinueStmt: label: Option<String>;
+// type DestructureStmt: target: Destructure, expr: Box<dyn Expr>, doc: Option<String>;
 // type ExprStmt: expr: Box<dyn Expr>;
+// type FnStmt: name: String, params: Rc<Vec<String>>, body: Rc<Vec<Box<dyn Stmt>>>, doc: Option<String>;
 // type IfStmt: expr: Box<dyn Expr>, stmt_true: Box<dyn Stmt>, stmt_false: Option<Box<dyn Stmt>>;
-// type PrintStmt: expr: Box<dyn Expr>;
-// type VarStmt: name: String, expr: Option<Box<dyn Expr>>;
-// type WhileStmt: expr: Box<dyn Expr>, stmt: Box<dyn Stmt>;
+// type ImportStmt: path: String, name: Option<String>, names: Vec<String>;
+// type MatchStmt: expr: Box<dyn Expr>, patterns: Vec<Pattern>, guards: Vec<Option<Box<dyn Expr>>>, arms: Vec<Box<dyn Stmt>>;
+// type ReturnStmt: expr: Option<Box<dyn Expr>>;
+// type ThrowStmt: expr: Box<dyn Expr>;
+// type TryStmt: stmt: Box<dyn Stmt>, name: Option<String>, catch_stmt: Option<Box<dyn Stmt>>, finally_stmt: Option<Box<dyn Stmt>>;
+// type VarStmt: name: String, expr: Option<Box<dyn Expr>>, constant: bool, doc: Option<String>;
+// type WhileStmt: label: Option<String>, expr: Box<dyn Expr>, stmt: Box<dyn Stmt>, incr: Option<Box<dyn Expr>>;
 
 // autogenerated code
 
@@ -25,15 +37,24 @@ pub trait Stmt
     fn len(&self) -> usize;
     fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt>;
 
-    fn run(&self, run: &mut VM) -> Result<(), Backtrace>;
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>;
 }
 
 trait Visitor<I>
 {
     fn visit_blockstmt(&mut self, expr: &BlockStmt) -> I;
+    fn visit_breakstmt(&mut self, expr: &BreakStmt) -> I;
+    fn visit_classstmt(&mut self, expr: &ClassStmt) -> I;
+    fn visit_continuestmt(&mut self, expr: &ContinueStmt) -> I;
+    fn visit_destructurestmt(&mut self, expr: &DestructureStmt) -> I;
     fn visit_exprstmt(&mut self, expr: &ExprStmt) -> I;
+    fn visit_fnstmt(&mut self, expr: &FnStmt) -> I;
     fn visit_ifstmt(&mut self, expr: &IfStmt) -> I;
-    fn visit_printstmt(&mut self, expr: &PrintStmt) -> I;
+    fn visit_importstmt(&mut self, expr: &ImportStmt) -> I;
+    fn visit_matchstmt(&mut self, expr: &MatchStmt) -> I;
+    fn visit_returnstmt(&mut self, expr: &ReturnStmt) -> I;
+    fn visit_throwstmt(&mut self, expr: &ThrowStmt) -> I;
+    fn visit_trystmt(&mut self, expr: &TryStmt) -> I;
     fn visit_varstmt(&mut self, expr: &VarStmt) -> I;
     fn visit_whilestmt(&mut self, expr: &WhileStmt) -> I;
 }
@@ -51,10 +72,83 @@ impl Stmt for BlockStmt
     fn len(&self) -> usize { self.len }
     fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
 
-    fn run(&self, run: &mut VM) -> Result<(), Backtrace>
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
     { run.visit_blockstmt(self) }
 }
 
+pub struct BreakStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub label: Option<String>
+}
+
+impl Stmt for BreakStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_breakstmt(self) }
+}
+
+pub struct ClassStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub name: String,
+    pub superclass: Option<Box<dyn Expr>>,
+    pub methods: Vec<FnStmt>,
+    pub doc: Option<String>
+}
+
+impl Stmt for ClassStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_classstmt(self) }
+}
+
+pub struct ContinueStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub label: Option<String>
+}
+
+impl Stmt for ContinueStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_continuestmt(self) }
+}
+
+pub struct DestructureStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub target: Destructure,
+    pub expr: Box<dyn Expr>,
+    pub doc: Option<String>
+}
+
+impl Stmt for DestructureStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_destructurestmt(self) }
+}
+
 pub struct ExprStmt
 {
     pub start: usize,
@@ -68,10 +162,30 @@ impl Stmt for ExprStmt
     fn len(&self) -> usize { self.len }
     fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { Some(*self) }
 
-    fn run(&self, run: &mut VM) -> Result<(), Backtrace>
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
     { run.visit_exprstmt(self) }
 }
 
+pub struct FnStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub name: String,
+    pub params: Rc<Vec<String>>,
+    pub body: Rc<Vec<Box<dyn Stmt>>>,
+    pub doc: Option<String>
+}
+
+impl Stmt for FnStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_fnstmt(self) }
+}
+
 pub struct IfStmt
 {
     pub start: usize,
@@ -87,25 +201,101 @@ impl Stmt for IfStmt
     fn len(&self) -> usize { self.len }
     fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
 
-    fn run(&self, run: &mut VM) -> Result<(), Backtrace>
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
     { run.visit_ifstmt(self) }
 }
 
-pub struct PrintStmt
+pub struct ImportStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub path: String,
+    pub name: Option<String>,
+    pub names: Vec<String>
+}
+
+impl Stmt for ImportStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_importstmt(self) }
+}
+
+pub struct MatchStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub expr: Box<dyn Expr>,
+    pub patterns: Vec<Pattern>,
+    pub guards: Vec<Option<Box<dyn Expr>>>,
+    pub arms: Vec<Box<dyn Stmt>>
+}
+
+impl Stmt for MatchStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_matchstmt(self) }
+}
+
+pub struct ReturnStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub expr: Option<Box<dyn Expr>>
+}
+
+impl Stmt for ReturnStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_returnstmt(self) }
+}
+
+pub struct ThrowStmt
 {
     pub start: usize,
     pub len: usize,
     pub expr: Box<dyn Expr>
 }
 
-impl Stmt for PrintStmt
+impl Stmt for ThrowStmt
+{
+    fn start(&self) -> usize { self.start }
+    fn len(&self) -> usize { self.len }
+    fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
+
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_throwstmt(self) }
+}
+
+pub struct TryStmt
+{
+    pub start: usize,
+    pub len: usize,
+    pub stmt: Box<dyn Stmt>,
+    pub name: Option<String>,
+    pub catch_stmt: Option<Box<dyn Stmt>>,
+    pub finally_stmt: Option<Box<dyn Stmt>>
+}
+
+impl Stmt for TryStmt
 {
     fn start(&self) -> usize { self.start }
     fn len(&self) -> usize { self.len }
     fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
 
-    fn run(&self, run: &mut VM) -> Result<(), Backtrace>
-    { run.visit_printstmt(self) }
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
+    { run.visit_trystmt(self) }
 }
 
 pub struct VarStmt
@@ -113,7 +303,9 @@ pub struct VarStmt
     pub start: usize,
     pub len: usize,
     pub name: String,
-    pub expr: Option<Box<dyn Expr>>
+    pub expr: Option<Box<dyn Expr>>,
+    pub constant: bool,
+    pub doc: Option<String>
 }
 
 impl Stmt for VarStmt
@@ -122,7 +314,7 @@ impl Stmt for VarStmt
     fn len(&self) -> usize { self.len }
     fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
 
-    fn run(&self, run: &mut VM) -> Result<(), Backtrace>
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
     { run.visit_varstmt(self) }
 }
 
@@ -130,8 +322,10 @@ pub struct WhileStmt
 {
     pub start: usize,
     pub len: usize,
+    pub label: Option<String>,
     pub expr: Box<dyn Expr>,
-    pub stmt: Box<dyn Stmt>
+    pub stmt: Box<dyn Stmt>,
+    pub incr: Option<Box<dyn Expr>>
 }
 
 impl Stmt for WhileStmt
@@ -140,6 +334,6 @@ impl Stmt for WhileStmt
     fn len(&self) -> usize { self.len }
     fn to_exprstmt(self: Box<Self>) -> Option<ExprStmt> { None }
 
-    fn run(&self, run: &mut VM) -> Result<(), Backtrace>
+    fn run(&self, run: &mut VM) -> Result<(), Unwind>
     { run.visit_whilestmt(self) }
 }
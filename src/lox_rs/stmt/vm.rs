@@ -1,10 +1,12 @@
+use std::collections::HashMap;
+
 use super::{
-    super::{LoxValue, VM},
+    super::{Backtrace, LoxClass, LoxClosure, LoxValue, VM},
     *,
 };
 
-impl Visitor<Result<(), Backtrace>> for VM {
-    fn visit_blockstmt(&mut self, block: &BlockStmt) -> Result<(), Backtrace> {
+impl Visitor<Result<(), Unwind>> for VM {
+    fn visit_blockstmt(&mut self, block: &BlockStmt) -> Result<(), Unwind> {
         self.new_scope();
         for stmt in &block.stmts {
             match stmt.run(self) {
@@ -19,14 +21,117 @@ impl Visitor<Result<(), Backtrace>> for VM {
         return Ok(());
     }
 
-    fn visit_exprstmt(&mut self, stmt: &ExprStmt) -> Result<(), Backtrace> {
+    fn visit_breakstmt(&mut self, stmt: &BreakStmt) -> Result<(), Unwind> {
+        Err(Unwind::Break(stmt.label.clone()))
+    }
+
+    fn visit_classstmt(&mut self, stmt: &ClassStmt) -> Result<(), Unwind> {
+        let superclass = if let Some(expr) = &stmt.superclass {
+            match expr.run(self) {
+                Ok(LoxValue::Class(class)) => Some(class),
+                Ok(_) => {
+                    return Err(Unwind::Error(Backtrace::starting_at(
+                        format!("superclass must be a class"),
+                        (expr.start(), expr.len()),
+                    )))
+                }
+                Err(err) => return Err(Unwind::Error(err)),
+            }
+        } else {
+            None
+        };
+        if let Some(superclass) = &superclass {
+            // methods of a subclass close over a scope holding their superclass
+            self.new_scope();
+            self.curr_scope
+                .borrow_mut()
+                .define("super".to_string(), LoxValue::Class(Rc::clone(superclass)))
+                .expect("a new scope has no constants");
+        }
+        let mut methods = HashMap::new();
+        for method in &stmt.methods {
+            methods.insert(
+                method.name.to_string(),
+                LoxClosure::new(
+                    method.name.to_string(),
+                    Rc::clone(&method.params),
+                    Rc::clone(&method.body),
+                    Rc::clone(&self.curr_scope),
+                    Rc::clone(&self.source),
+                    method.name == "init",
+                ),
+            );
+        }
+        if superclass.is_some() {
+            self.unscope();
+        }
+        let class = LoxClass::new(stmt.name.to_string(), superclass, methods);
+        let mut scope = self.curr_scope.borrow_mut();
+        match scope.define(stmt.name.to_string(), LoxValue::Class(Rc::new(class))) {
+            Ok(()) => {
+                scope.document(stmt.name.to_string(), &stmt.doc);
+                Ok(())
+            }
+            Err(msg) => Err(Unwind::Error(Backtrace::starting_at(
+                msg,
+                (stmt.start, stmt.len),
+            ))),
+        }
+    }
+
+    fn visit_continuestmt(&mut self, stmt: &ContinueStmt) -> Result<(), Unwind> {
+        Err(Unwind::Continue(stmt.label.clone()))
+    }
+
+    fn visit_destructurestmt(&mut self, stmt: &DestructureStmt) -> Result<(), Unwind> {
+        let value = match stmt.expr.run(self) {
+            Ok(value) => value,
+            Err(err) => return Err(Unwind::Error(err)),
+        };
+        let mut bindings = Vec::new();
+        if let Err(err) = stmt.target.bind(value, &mut bindings) {
+            return Err(Unwind::Error(err));
+        }
+        for (name, span, value) in bindings {
+            let mut scope = self.curr_scope.borrow_mut();
+            if let Err(msg) = scope.define(name.clone(), value) {
+                return Err(Unwind::Error(Backtrace::starting_at(msg, span)));
+            }
+            scope.document(name, &stmt.doc);
+        }
+        Ok(())
+    }
+
+    fn visit_exprstmt(&mut self, stmt: &ExprStmt) -> Result<(), Unwind> {
         match stmt.expr.run(self) {
             Ok(_) => Ok(()),
-            Err(err) => Err(err),
+            Err(err) => Err(Unwind::Error(err)),
         }
     }
 
-    fn visit_ifstmt(&mut self, stmt: &IfStmt) -> Result<(), Backtrace> {
+    fn visit_fnstmt(&mut self, stmt: &FnStmt) -> Result<(), Unwind> {
+        let closure = LoxClosure::new(
+            stmt.name.to_string(),
+            Rc::clone(&stmt.params),
+            Rc::clone(&stmt.body),
+            Rc::clone(&self.curr_scope),
+            Rc::clone(&self.source),
+            false,
+        );
+        let mut scope = self.curr_scope.borrow_mut();
+        match scope.define(stmt.name.to_string(), LoxValue::Fn(closure)) {
+            Ok(()) => {
+                scope.document(stmt.name.to_string(), &stmt.doc);
+                Ok(())
+            }
+            Err(msg) => Err(Unwind::Error(Backtrace::starting_at(
+                msg,
+                (stmt.start, stmt.len),
+            ))),
+        }
+    }
+
+    fn visit_ifstmt(&mut self, stmt: &IfStmt) -> Result<(), Unwind> {
         match stmt.expr.run(self) {
             Ok(value) => {
                 if LoxValue::is_truthy(&value) {
@@ -37,36 +142,168 @@ impl Visitor<Result<(), Backtrace>> for VM {
                     Ok(())
                 }
             }
-            Err(err) => Err(err),
+            Err(err) => Err(Unwind::Error(err)),
         }
     }
 
-    fn visit_printstmt(&mut self, stmt: &PrintStmt) -> Result<(), Backtrace> {
-        match stmt.expr.run(self) {
-            Ok(value) => {
-                println!("{value}");
-                Ok(())
+    fn visit_importstmt(&mut self, stmt: &ImportStmt) -> Result<(), Unwind> {
+        let module = match self.import(&stmt.path) {
+            Ok(module) => module,
+            Err(mut err) => {
+                err.push((stmt.start, stmt.len));
+                return Err(Unwind::Error(err));
+            }
+        };
+        if let Some(name) = &stmt.name {
+            if let Err(msg) = self
+                .curr_scope
+                .borrow_mut()
+                .define(name.to_string(), LoxValue::Module(Rc::clone(&module)))
+            {
+                return Err(Unwind::Error(Backtrace::starting_at(
+                    msg,
+                    (stmt.start, stmt.len),
+                )));
+            }
+        }
+        for name in &stmt.names {
+            // names imported from constants stay constant
+            let result = match module.get(name) {
+                Some(value) if module.is_constant(name) => self
+                    .curr_scope
+                    .borrow_mut()
+                    .define_const(name.to_string(), value),
+                Some(value) => self.curr_scope.borrow_mut().define(name.to_string(), value),
+                None => Err(format!("module '{}' has no member '{}'", module.name, name)),
+            };
+            if let Err(msg) = result {
+                return Err(Unwind::Error(Backtrace::starting_at(
+                    msg,
+                    (stmt.start, stmt.len),
+                )));
             }
-            Err(err) => Err(err),
         }
+        Ok(())
     }
 
-    fn visit_varstmt(&mut self, stmt: &VarStmt) -> Result<(), Backtrace> {
+    fn visit_matchstmt(&mut self, stmt: &MatchStmt) -> Result<(), Unwind> {
+        let value = match stmt.expr.run(self) {
+            Ok(value) => value,
+            Err(err) => return Err(Unwind::Error(err)),
+        };
+        for (i, pattern) in stmt.patterns.iter().enumerate() {
+            if !pattern.matches(&value) {
+                continue;
+            }
+            // each arm gets a fresh scope for the name its pattern binds
+            self.new_scope();
+            if let Some(name) = pattern.binding() {
+                self.curr_scope
+                    .borrow_mut()
+                    .define(name.to_string(), value.clone())
+                    .expect("a new scope has no constants");
+            }
+            if let Some(guard) = &stmt.guards[i] {
+                match guard.run(self) {
+                    Ok(result) if LoxValue::is_truthy(&result) => {}
+                    Ok(_) => {
+                        self.unscope();
+                        continue;
+                    }
+                    Err(err) => {
+                        self.unscope();
+                        return Err(Unwind::Error(err));
+                    }
+                }
+            }
+            let result = stmt.arms[i].run(self);
+            self.unscope();
+            return result;
+        }
+        Ok(())
+    }
+
+    fn visit_returnstmt(&mut self, stmt: &ReturnStmt) -> Result<(), Unwind> {
         if let Some(expr) = &stmt.expr {
             match expr.run(self) {
-                Ok(value) => {
-                    self.curr_scope.define(stmt.name.to_string(), value);
-                    Ok(())
+                Ok(value) => Err(Unwind::Return(value)),
+                Err(err) => Err(Unwind::Error(err)),
+            }
+        } else {
+            Err(Unwind::Return(LoxValue::Nil))
+        }
+    }
+
+    fn visit_throwstmt(&mut self, stmt: &ThrowStmt) -> Result<(), Unwind> {
+        match stmt.expr.run(self) {
+            Ok(value) => Err(Unwind::Error(Backtrace::thrown(
+                value,
+                (stmt.start, stmt.len),
+            ))),
+            Err(err) => Err(Unwind::Error(err)),
+        }
+    }
+
+    fn visit_trystmt(&mut self, stmt: &TryStmt) -> Result<(), Unwind> {
+        // blocks unscope as they unwind, but restore the scope anyway so handlers
+        // always run where the try statement is
+        let scope = Rc::clone(&self.curr_scope);
+        let result = match (stmt.stmt.run(self), &stmt.catch_stmt) {
+            (Err(Unwind::Error(err)), Some(catch_stmt)) => {
+                self.curr_scope = Rc::clone(&scope);
+                self.new_scope();
+                if let Some(name) = &stmt.name {
+                    let value = self.error_object(&err);
+                    self.curr_scope
+                        .borrow_mut()
+                        .define(name.to_string(), value)
+                        .expect("a new scope has no constants");
                 }
-                Err(err) => Err(err),
+                let result = catch_stmt.run(self);
+                self.unscope();
+                result
             }
+            (result, _) => result,
+        };
+        if let Some(finally_stmt) = &stmt.finally_stmt {
+            self.curr_scope = scope;
+            // a jump or error out of the finally block replaces the pending one
+            if let Err(unwind) = finally_stmt.run(self) {
+                return Err(unwind);
+            }
+        }
+        result
+    }
+
+    fn visit_varstmt(&mut self, stmt: &VarStmt) -> Result<(), Unwind> {
+        let value = match &stmt.expr {
+            Some(expr) => match expr.run(self) {
+                Ok(value) => value,
+                Err(err) => return Err(Unwind::Error(err)),
+            },
+            None => LoxValue::Nil,
+        };
+        let mut scope = self.curr_scope.borrow_mut();
+        let result = if stmt.constant {
+            scope.define_const(stmt.name.to_string(), value)
         } else {
-            self.curr_scope.define(stmt.name.to_string(), LoxValue::Nil);
-            Ok(())
+            scope.define(stmt.name.to_string(), value)
+        };
+        match result {
+            Ok(()) => {
+                scope.document(stmt.name.to_string(), &stmt.doc);
+                Ok(())
+            }
+            Err(msg) => Err(Unwind::Error(Backtrace::starting_at(
+                msg,
+                (stmt.start, stmt.len),
+            ))),
         }
     }
 
-    fn visit_whilestmt(&mut self, stmt: &WhileStmt) -> Result<(), Backtrace> {
+    fn visit_whilestmt(&mut self, stmt: &WhileStmt) -> Result<(), Unwind> {
+        // unlabeled break and continue target the innermost loop
+        let targets_loop = |label: &Option<String>| label.is_none() || *label == stmt.label;
         loop {
             match stmt.expr.run(self) {
                 Ok(value) => {
@@ -74,11 +311,19 @@ impl Visitor<Result<(), Backtrace>> for VM {
                         return Ok(());
                     }
                     match stmt.stmt.run(self) {
+                        Err(Unwind::Break(label)) if targets_loop(&label) => return Ok(()),
+                        Err(Unwind::Continue(label)) if targets_loop(&label) => {}
                         Err(err) => return Err(err),
                         _ => {}
                     }
+                    if let Some(incr) = &stmt.incr {
+                        match incr.run(self) {
+                            Err(err) => return Err(Unwind::Error(err)),
+                            _ => {}
+                        }
+                    }
                 }
-                Err(err) => return Err(err),
+                Err(err) => return Err(Unwind::Error(err)),
             }
         }
     }
@@ -0,0 +1,8 @@
+use super::{Backtrace, LoxValue};
+
+pub enum Unwind {
+    Error(Backtrace),
+    Return(LoxValue),
+    Break(Option<String>),
+    Continue(Option<String>),
+}
@@ -1,47 +1,222 @@
-use std::collections::HashMap;
-use std::mem::swap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
-use super::{Backtrace, Errors, LoxValue, Parser};
+use super::{
+    builtins, stmt::Stmt, Backtrace, Errors, LoxClass, LoxInstance, LoxModule, LoxNative, LoxValue,
+    Parser, Source, Unwind,
+};
 
 pub struct VM {
-    pub curr_scope: Scope,
+    // the builtins; the main script and each module run in their own scope inside it
+    pub globals: Rc<RefCell<Scope>>,
+    pub curr_scope: Rc<RefCell<Scope>>,
+    pub source: Rc<Source>,
+    error_class: Rc<LoxClass>,
+    modules: HashMap<PathBuf, Rc<LoxModule>>,
+    importing: Vec<PathBuf>,
 }
 
 impl VM {
     pub fn new() -> VM {
-        VM {
-            curr_scope: Scope::new(),
-        }
+        let globals = Rc::new(RefCell::new(Scope::new()));
+        let mut vm = VM {
+            curr_scope: Rc::new(RefCell::new(Scope::new_inner(Rc::clone(&globals)))),
+            globals,
+            source: Rc::new(Source {
+                path: None,
+                code: String::new(),
+            }),
+            error_class: Rc::new(LoxClass::new("Error".to_string(), None, HashMap::new())),
+            modules: HashMap::new(),
+            importing: Vec::new(),
+        };
+        builtins::install(&mut vm);
+        vm
+    }
+
+    pub fn define_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        func: impl Fn(&mut VM, Vec<LoxValue>) -> Result<LoxValue, String> + 'static,
+    ) {
+        let native = LoxNative::new(name.to_string(), arity, Rc::new(func));
+        self.globals
+            .borrow_mut()
+            .define(name.to_string(), LoxValue::Native(native))
+            .expect("builtins are never constant");
     }
 
     pub fn run(&mut self, code: &str) {
-        let mut errors: Errors = Errors::new(code);
-        let mut parser: Parser = Parser::new(code);
-        while let Some(stmt) = parser.next() {
-            match stmt.run(self) {
-                Ok(()) => {}
-                Err(err) => {
-                    Self::print_backtrace(code, "Runtime", err);
+        self.run_source(Rc::new(Source {
+            path: None,
+            code: code.to_string(),
+        }));
+    }
+
+    pub fn run_file(&mut self, path: &Path, code: &str) {
+        // the script itself counts as being imported, so importing it back is a cycle
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.importing.push(path.clone());
+        self.run_source(Rc::new(Source {
+            path: Some(path),
+            code: code.to_string(),
+        }));
+        self.importing.pop();
+    }
+
+    fn run_source(&mut self, source: Rc<Source>) {
+        let caller_source = std::mem::replace(&mut self.source, Rc::clone(&source));
+        if let Some(stmts) = Self::parse(&source, None) {
+            for stmt in &stmts {
+                match stmt.run(self) {
+                    Ok(()) => {}
+                    Err(Unwind::Error(mut err)) => {
+                        err.set_source(&source);
+                        Self::print_backtrace(&source, "Runtime", err);
+                    }
+                    // a top-level return, break or continue has already been reported by the parser
+                    Err(Unwind::Return(_)) | Err(Unwind::Break(_)) | Err(Unwind::Continue(_)) => {}
                 }
             }
         }
+        self.source = caller_source;
+    }
+
+    /// parse: parse all of a source and print its diagnostics, under a heading naming
+    /// the file if one is given. Returns None if there were errors, so a syntax error
+    /// keeps all of the source from running.
+    fn parse(source: &Source, heading: Option<&Path>) -> Option<Vec<Box<dyn Stmt>>> {
+        let mut errors: Errors = Errors::new(&source.code);
+        let mut parser: Parser = Parser::new(&source.code);
+        let stmts: Vec<Box<dyn Stmt>> = parser.by_ref().collect();
         parser.coalesce_errors(&mut errors);
+        if let Some(path) = heading {
+            if !errors.is_empty() {
+                println!("In {}:", path.display());
+            }
+        }
         errors.print_errors(Box::new(|code, msg, sev, start, len| {
             Self::print_error(code, msg, sev, start, len)
         }));
+        if errors.has_errors() {
+            None
+        } else {
+            Some(stmts)
+        }
+    }
+
+    /// import: load a module, running its file the first time it is imported.
+    /// Paths are relative to the directory of the importing file.
+    pub fn import(&mut self, path: &str) -> Result<Rc<LoxModule>, Backtrace> {
+        let dir = match &self.source.path {
+            Some(file) => file.parent().map(Path::to_path_buf).unwrap_or_default(),
+            None => PathBuf::new(),
+        };
+        let full_path = match fs::canonicalize(dir.join(path)) {
+            Ok(full_path) => full_path,
+            Err(err) => return Err(Backtrace::new(format!("can't import \"{path}\": {err}"))),
+        };
+        if let Some(module) = self.modules.get(&full_path) {
+            return Ok(Rc::clone(module));
+        }
+        if let Some(i) = self.importing.iter().position(|file| *file == full_path) {
+            let cycle = self.importing[i..]
+                .iter()
+                .chain([&full_path])
+                .map(|file| file.display().to_string())
+                .collect::<Vec<String>>()
+                .join(" -> ");
+            return Err(Backtrace::new(format!("import cycle: {cycle}")));
+        }
+        let code = match fs::read_to_string(&full_path) {
+            Ok(code) => code,
+            Err(err) => return Err(Backtrace::new(format!("can't import \"{path}\": {err}"))),
+        };
+        let source = Rc::new(Source {
+            path: Some(full_path.clone()),
+            code,
+        });
+
+        let stmts = match Self::parse(&source, Some(&full_path)) {
+            Some(stmts) => stmts,
+            None => {
+                return Err(Backtrace::new(format!(
+                    "can't import \"{path}\": it has syntax errors"
+                )))
+            }
+        };
+
+        let scope = Rc::new(RefCell::new(Scope::new_inner(Rc::clone(&self.globals))));
+        let caller_scope = std::mem::replace(&mut self.curr_scope, Rc::clone(&scope));
+        let caller_source = std::mem::replace(&mut self.source, Rc::clone(&source));
+        self.importing.push(full_path.clone());
+        let mut result = Ok(());
+        for stmt in &stmts {
+            match stmt.run(self) {
+                Ok(()) => {}
+                Err(Unwind::Error(mut err)) => {
+                    err.set_source(&source);
+                    result = Err(err);
+                    break;
+                }
+                Err(Unwind::Return(_)) | Err(Unwind::Break(_)) | Err(Unwind::Continue(_)) => {}
+            }
+        }
+        self.importing.pop();
+        self.curr_scope = caller_scope;
+        self.source = caller_source;
+        match result {
+            Ok(()) => {
+                let name = match full_path.file_stem() {
+                    Some(stem) => stem.to_string_lossy().to_string(),
+                    None => path.to_string(),
+                };
+                let module = Rc::new(LoxModule::new(name, scope));
+                self.modules.insert(full_path, Rc::clone(&module));
+                Ok(module)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// error_object: the value a catch block receives for an error.
+    /// Thrown values are caught as they are; runtime errors become Error instances
+    /// with the message and a list of [start, length] spans, innermost first.
+    pub fn error_object(&self, err: &Backtrace) -> LoxValue {
+        if let Some(value) = err.get_thrown() {
+            return value;
+        }
+        let spans = err
+            .iter()
+            .map(|(start, len)| {
+                LoxValue::List(Rc::new(RefCell::new(vec![
+                    LoxValue::Int(*start as i64),
+                    LoxValue::Int(*len as i64),
+                ])))
+            })
+            .collect();
+        let mut instance = LoxInstance::new(Rc::clone(&self.error_class));
+        instance.set("message".to_string(), LoxValue::Str(err.get_error()));
+        instance.set(
+            "spans".to_string(),
+            LoxValue::List(Rc::new(RefCell::new(spans))),
+        );
+        LoxValue::Instance(Rc::new(RefCell::new(instance)))
     }
 
     pub fn new_scope(&mut self) {
-        let mut scope = Scope::new();
-        swap(&mut scope, &mut self.curr_scope);
-        self.curr_scope = Scope::new_inner(scope);
+        let scope = Scope::new_inner(Rc::clone(&self.curr_scope));
+        self.curr_scope = Rc::new(RefCell::new(scope));
     }
 
     pub fn unscope(&mut self) {
-        let mut scope = Scope::new();
-        swap(&mut scope, &mut self.curr_scope);
-        if let Some(scope) = scope.unscope() {
-            self.curr_scope = *scope;
+        let outer = self.curr_scope.borrow().outer.clone();
+        if let Some(scope) = outer {
+            self.curr_scope = scope;
         }
     }
 
@@ -87,12 +262,14 @@ impl VM {
         }
     }
 
-    fn print_backtrace(code: &str, sev: &str, backtrace: Backtrace) {
+    fn print_backtrace(main: &Rc<Source>, sev: &str, backtrace: Backtrace) {
         let msg = backtrace.get_error();
         println!("{sev}: {msg}");
         println!("Backtrace: ");
-        backtrace.iter().for_each(|site| {
+        backtrace.sites().for_each(|(site, source)| {
             let (start, len) = *site;
+            let source = source.as_ref().unwrap_or(main);
+            let code = &source.code;
             let mut index = 0;
             let mut line = 1;
             let mut line_start = 0;
@@ -112,7 +289,14 @@ impl VM {
                     break;
                 }
             }
-            let line_prefix = format!("line {line}: ");
+            // sites in other files are labeled with the file they are in
+            let line_prefix = match &source.path {
+                Some(path) if !Rc::ptr_eq(source, main) => match path.file_name() {
+                    Some(file) => format!("{} line {line}: ", file.to_string_lossy()),
+                    None => format!("line {line}: "),
+                },
+                _ => format!("line {line}: "),
+            };
             println!("{line_prefix}{}", &code[line_start..line_next - 1]);
             if len != 0 {
                 println!(
@@ -137,50 +321,92 @@ impl VM {
 
 pub struct Scope {
     pub vars: HashMap<String, LoxValue>,
-    pub outer: Option<Box<Scope>>,
+    pub constants: HashSet<String>,
+    // doc comments of the declarations that bound names here
+    pub docs: HashMap<String, String>,
+    pub outer: Option<Rc<RefCell<Scope>>>,
 }
 
 impl Scope {
     pub fn new() -> Scope {
         Scope {
             vars: HashMap::new(),
+            constants: HashSet::new(),
+            docs: HashMap::new(),
             outer: None,
         }
     }
 
-    pub fn new_inner(outer: Scope) -> Scope {
+    pub fn new_inner(outer: Rc<RefCell<Scope>>) -> Scope {
         Scope {
             vars: HashMap::new(),
-            outer: Some(Box::new(outer)),
+            constants: HashSet::new(),
+            docs: HashMap::new(),
+            outer: Some(outer),
         }
     }
 
-    pub fn define(&mut self, name: String, value: LoxValue) {
+    /// define: bind a name in this scope, failing if it is already a constant here.
+    pub fn define(&mut self, name: String, value: LoxValue) -> Result<(), String> {
+        if self.constants.contains(&name) {
+            return Err(format!("can't assign to constant '{name}'"));
+        }
+        // a redefinition doesn't keep the old declaration's doc comment
+        self.docs.remove(&name);
         self.vars.insert(name, value);
+        Ok(())
     }
 
-    pub fn get(&mut self, name: &String) -> Option<&LoxValue> {
-        if let Some(value) = self.vars.get(name) {
-            Some(value)
-        } else if let Some(outer) = self.outer.as_mut() {
-            outer.get(name)
+    /// document: attach the doc comment of the declaration that defined a name here.
+    pub fn document(&mut self, name: String, doc: &Option<String>) {
+        if let Some(doc) = doc {
+            self.docs.insert(name, doc.to_string());
+        }
+    }
+
+    /// doc: the doc comment of the innermost binding of a name, if it has one.
+    pub fn doc(&self, name: &String) -> Option<String> {
+        if self.vars.contains_key(name) {
+            self.docs.get(name).cloned()
+        } else if let Some(outer) = &self.outer {
+            outer.borrow().doc(name)
         } else {
             None
         }
     }
 
-    pub fn set(&mut self, name: String, value: LoxValue) -> bool {
-        if self.vars.contains_key(&name) {
-            self.vars.insert(name, value);
-            true
-        } else if let Some(outer) = self.outer.as_mut() {
-            outer.set(name, value)
+    pub fn define_const(&mut self, name: String, value: LoxValue) -> Result<(), String> {
+        match self.define(name.clone(), value) {
+            Ok(()) => {
+                self.constants.insert(name);
+                Ok(())
+            }
+            Err(msg) => Err(msg),
+        }
+    }
+
+    pub fn get(&self, name: &String) -> Option<LoxValue> {
+        if let Some(value) = self.vars.get(name) {
+            Some(value.clone())
+        } else if let Some(outer) = &self.outer {
+            outer.borrow().get(name)
         } else {
-            false
+            None
         }
     }
 
-    pub fn unscope(self) -> Option<Box<Scope>> {
-        self.outer
+    /// set: assign to the innermost binding of a name, failing with the reason
+    /// if the name is undefined or bound by a constant.
+    pub fn set(&mut self, name: String, value: LoxValue) -> Result<(), String> {
+        if self.constants.contains(&name) {
+            Err(format!("can't assign to constant '{name}'"))
+        } else if self.vars.contains_key(&name) {
+            self.vars.insert(name, value);
+            Ok(())
+        } else if let Some(outer) = &self.outer {
+            outer.borrow_mut().set(name, value)
+        } else {
+            Err(format!("undefined variable"))
+        }
     }
 }
@@ -3,6 +3,7 @@ mod lox_rs;
 use std::{
     env, fs,
     io::{self, Write},
+    path::Path,
     str,
 };
 
@@ -23,7 +24,7 @@ fn main() {
 fn run_file(mut vm: VM, file_name: &String) {
     match fs::read(file_name) {
         Ok(data) => match str::from_utf8(&data) {
-            Ok(text) => vm.run(text),
+            Ok(text) => vm.run_file(Path::new(file_name), text),
             Err(err) => println!("Error reading {file_name}: {err}"),
         },
         Err(err) => println!("Error reading {file_name}: {err}"),